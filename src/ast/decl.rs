@@ -1,28 +1,63 @@
 use std::fmt::{Debug, Formatter};
 
-use super::{expr::Expr, types::Type};
+use crate::token::CursorPos;
 
+use super::{expr::Expr, pattern::Pattern, types::Type};
+
+#[derive(Clone)]
 pub struct Decl {
     pub name: String,
+    pub pattern: Option<Pattern>,
     pub r#type: Option<Type>,
     pub value: Option<Expr>,
+    pub is_mut: bool,
+    pub is_pub: bool,
+    // Declared with `const`, so the value must be known at compile time
+    pub is_const: bool,
+    // The value was evaluated at compile time and initialises the global directly
+    pub is_const_init: bool,
+    // Storage whose address outlives the function is allocated on the heap
+    pub is_escaping: bool,
     pub value_id: Option<u64>,
+    pub pos: Option<CursorPos>,
 }
 
 impl Decl {
-    pub fn new(name: String, r#type: Option<Type>, value: Option<Expr>) -> Self {
+    pub fn new(name: String, r#type: Option<Type>, value: Option<Expr>, is_mut: bool) -> Self {
         Self {
             name,
+            pattern: None,
             r#type,
             value,
+            is_mut,
+            is_pub: false,
+            is_const: false,
+            is_const_init: false,
+            is_escaping: false,
             value_id: None,
+            pos: None,
         }
     }
 }
 
 impl Debug for Decl {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "let {}", self.name)?;
+        if self.is_pub {
+            write!(f, "pub ")?;
+        }
+        if self.is_const {
+            write!(f, "const ")?;
+        } else {
+            write!(f, "let ")?;
+        }
+        if self.is_mut {
+            write!(f, "mut ")?;
+        }
+        if let Some(pattern) = &self.pattern {
+            write!(f, "{:?}", pattern)?;
+        } else {
+            write!(f, "{}", self.name)?;
+        }
         if let Some(value_id) = self.value_id {
             write!(f, "({})", value_id)?;
         }
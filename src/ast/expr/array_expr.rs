@@ -0,0 +1,48 @@
+use std::fmt::Debug;
+
+use crate::{ast::types::Type, utils::join_list};
+
+use super::{Expr, ExprKind, ExprTrait};
+
+#[derive(Clone)]
+pub struct ArrayExpr {
+    pub elems: Vec<Expr>,
+    pub r#type: Option<Type>,
+}
+
+impl ArrayExpr {
+    pub fn new(elems: Vec<Expr>) -> Self {
+        Self {
+            elems,
+            r#type: None,
+        }
+    }
+}
+
+impl ExprTrait for ArrayExpr {
+    fn kind(&self) -> ExprKind {
+        ExprKind::Array
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn ExprTrait> {
+        Box::new(self.clone())
+    }
+
+    fn r#type(&self) -> &Option<Type> {
+        &self.r#type
+    }
+}
+
+impl Debug for ArrayExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}]", join_list(&self.elems, ", "))
+    }
+}
@@ -0,0 +1,50 @@
+use std::fmt::Debug;
+
+use crate::ast::types::Type;
+
+use super::{Expr, ExprKind, ExprTrait};
+
+#[derive(Clone)]
+pub struct ArrayRepeatExpr {
+    pub value: Expr,
+    pub len: u32,
+    pub r#type: Option<Type>,
+}
+
+impl ArrayRepeatExpr {
+    pub fn new(value: Expr, len: u32) -> Self {
+        Self {
+            value,
+            len,
+            r#type: None,
+        }
+    }
+}
+
+impl ExprTrait for ArrayRepeatExpr {
+    fn kind(&self) -> ExprKind {
+        ExprKind::ArrayRepeat
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn ExprTrait> {
+        Box::new(self.clone())
+    }
+
+    fn r#type(&self) -> &Option<Type> {
+        &self.r#type
+    }
+}
+
+impl Debug for ArrayRepeatExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{:?}; {}]", self.value, self.len)
+    }
+}
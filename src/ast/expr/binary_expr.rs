@@ -0,0 +1,110 @@
+use std::fmt::Debug;
+
+use crate::ast::types::Type;
+
+use super::{Expr, ExprKind, ExprTrait};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Ne,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    Coalesce,
+}
+
+impl BinaryOp {
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Self::Coalesce => 1,
+            Self::Eq | Self::Ne | Self::Lt | Self::Gt | Self::Le | Self::Ge => 2,
+            Self::Add | Self::Sub => 3,
+            Self::Mul | Self::Div => 4,
+        }
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        self.precedence() == 2
+    }
+
+    pub fn is_right_assoc(&self) -> bool {
+        matches!(self, Self::Coalesce)
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Gt => ">",
+            Self::Le => "<=",
+            Self::Ge => ">=",
+            Self::Coalesce => "??",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct BinaryExpr {
+    pub op: BinaryOp,
+    pub lhs: Expr,
+    pub rhs: Expr,
+    // Operator method of composite operands, called with swapped operands and/or a
+    // negated result for operators derived from another one
+    pub method_id: Option<u64>,
+    pub is_swapped: bool,
+    pub is_negated: bool,
+    pub r#type: Option<Type>,
+}
+
+impl BinaryExpr {
+    pub fn new(op: BinaryOp, lhs: Expr, rhs: Expr) -> Self {
+        Self {
+            op,
+            lhs,
+            rhs,
+            method_id: None,
+            is_swapped: false,
+            is_negated: false,
+            r#type: None,
+        }
+    }
+}
+
+impl ExprTrait for BinaryExpr {
+    fn kind(&self) -> ExprKind {
+        ExprKind::Binary
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn ExprTrait> {
+        Box::new(self.clone())
+    }
+
+    fn r#type(&self) -> &Option<Type> {
+        &self.r#type
+    }
+}
+
+impl Debug for BinaryExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({:?} {} {:?})", self.lhs, self.op.symbol(), self.rhs)
+    }
+}
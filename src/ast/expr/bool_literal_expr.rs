@@ -0,0 +1,48 @@
+use std::{any::Any, fmt::Debug};
+
+use crate::ast::types::{BoolType, Type};
+
+use super::{ExprKind, ExprTrait};
+
+#[derive(Clone)]
+pub struct BoolLiteralExpr {
+    pub value: bool,
+    pub r#type: Option<Type>,
+}
+
+impl BoolLiteralExpr {
+    pub fn new(value: bool) -> Self {
+        Self {
+            value,
+            r#type: Some(BoolType.into()),
+        }
+    }
+}
+
+impl ExprTrait for BoolLiteralExpr {
+    fn kind(&self) -> ExprKind {
+        ExprKind::BoolLiteral
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn ExprTrait> {
+        Box::new(self.clone())
+    }
+
+    fn r#type(&self) -> &Option<Type> {
+        &self.r#type
+    }
+}
+
+impl Debug for BoolLiteralExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
@@ -11,6 +11,8 @@ use super::{Expr, ExprKind, ExprTrait};
 pub struct CallExpr {
     pub postfix_expr: Expr,
     pub args: Vec<Arg>,
+    // A temporary receiver is copied to the heap when the method keeps a reference to it
+    pub is_escaping: bool,
     pub r#type: Option<Type>,
 }
 
@@ -19,6 +21,7 @@ impl CallExpr {
         Self {
             postfix_expr,
             args,
+            is_escaping: false,
             r#type: None,
         }
     }
@@ -8,6 +8,8 @@ use super::{Expr, ExprKind, ExprTrait};
 pub struct CompositeExpr {
     pub fields: Vec<(String, Expr)>,
     pub r#type: Option<Type>,
+    // Index of each field in the composite type, set by the checker
+    pub field_indices: Vec<u32>,
 }
 
 impl CompositeExpr {
@@ -15,6 +17,7 @@ impl CompositeExpr {
         Self {
             fields,
             r#type: None,
+            field_indices: Vec::new(),
         }
     }
 }
@@ -0,0 +1,69 @@
+use std::{any::Any, fmt::Debug};
+
+use crate::ast::types::Type;
+
+use super::{Expr, ExprKind, ExprTrait};
+
+// Wraps a value of a type implementing an interface into a (data pointer, vtable) pair
+#[derive(Clone)]
+pub struct DynExpr {
+    pub expr: Expr,
+    pub type_id: u64,
+    pub interface_id: u64,
+    pub method_ids: Vec<u64>,
+    // The data is copied to the heap when the value outlives the function creating it
+    pub is_escaping: bool,
+    pub r#type: Option<Type>,
+}
+
+impl DynExpr {
+    pub fn new(
+        expr: Expr,
+        type_id: u64,
+        interface_id: u64,
+        method_ids: Vec<u64>,
+        r#type: Type,
+    ) -> Self {
+        Self {
+            expr,
+            type_id,
+            interface_id,
+            method_ids,
+            is_escaping: false,
+            r#type: Some(r#type),
+        }
+    }
+}
+
+impl ExprTrait for DynExpr {
+    fn kind(&self) -> ExprKind {
+        ExprKind::Dyn
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn ExprTrait> {
+        Box::new(self.clone())
+    }
+
+    fn r#type(&self) -> &Option<Type> {
+        &self.r#type
+    }
+}
+
+impl Debug for DynExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "({:?} as dyn {:?})",
+            self.expr,
+            self.r#type.as_ref().unwrap()
+        )
+    }
+}
@@ -0,0 +1,92 @@
+use std::{any::Any, fmt::Debug};
+
+use crate::{
+    ast::{param::Param, stmt::Stmt, type_param::TypeParam, types::Type},
+    utils::join_list,
+};
+
+use super::{ExprKind, ExprTrait};
+
+#[derive(Clone)]
+pub struct FuncExpr {
+    pub type_params: Vec<TypeParam>,
+    pub params: Vec<Param>,
+    pub return_type: Option<Type>,
+    pub is_method: bool,
+    pub is_var_args: bool,
+    pub body: Vec<Stmt>,
+    pub r#type: Option<Type>,
+    // Whether control can reach the end of the body, set by the checker
+    pub is_end_reachable: bool,
+}
+
+impl FuncExpr {
+    pub fn new(
+        params: Vec<Param>,
+        return_type: Option<Type>,
+        is_method: bool,
+        body: Vec<Stmt>,
+    ) -> Self {
+        Self {
+            type_params: Vec::new(),
+            params,
+            return_type,
+            is_method,
+            is_var_args: false,
+            body,
+            r#type: None,
+            is_end_reachable: true,
+        }
+    }
+}
+
+impl ExprTrait for FuncExpr {
+    fn kind(&self) -> ExprKind {
+        ExprKind::Func
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn ExprTrait> {
+        Box::new(self.clone())
+    }
+
+    fn r#type(&self) -> &Option<Type> {
+        &self.r#type
+    }
+}
+
+impl Debug for FuncExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{{{")?;
+        if !self.type_params.is_empty() {
+            write!(f, "<{}> ", join_list(&self.type_params, ", "))?;
+        }
+        if self.is_method && self.params.first().is_none_or(|param| param.name != "self") {
+            write!(f, "self")?;
+            if !self.params.is_empty() {
+                write!(f, ", ")?;
+            }
+        }
+        if self.is_var_args {
+            let (last, params) = self.params.split_last().unwrap();
+            write!(f, "{}", join_list(params, ", "))?;
+            if !params.is_empty() {
+                write!(f, ", ")?;
+            }
+            write!(f, "...{:?}", last)?;
+        } else {
+            write!(f, "{}", join_list(&self.params, ", "))?;
+        }
+        if let Some(return_type) = &self.return_type {
+            write!(f, " -> {:?}", return_type)?;
+        }
+        write!(f, ";\n{}\n}}}}", join_list(&self.body, "\n"))
+    }
+}
@@ -0,0 +1,64 @@
+use std::{any::Any, fmt::Debug};
+
+use crate::{
+    ast::{stmt::Stmt, types::Type},
+    utils::join_list,
+};
+
+use super::{Expr, ExprKind, ExprTrait};
+
+#[derive(Clone)]
+pub struct IfExpr {
+    pub cond: Expr,
+    pub then_body: Vec<Stmt>,
+    pub else_body: Option<Vec<Stmt>>,
+    pub r#type: Option<Type>,
+}
+
+impl IfExpr {
+    pub fn new(cond: Expr, then_body: Vec<Stmt>, else_body: Option<Vec<Stmt>>) -> Self {
+        Self {
+            cond,
+            then_body,
+            else_body,
+            r#type: None,
+        }
+    }
+}
+
+impl ExprTrait for IfExpr {
+    fn kind(&self) -> ExprKind {
+        ExprKind::If
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn ExprTrait> {
+        Box::new(self.clone())
+    }
+
+    fn r#type(&self) -> &Option<Type> {
+        &self.r#type
+    }
+}
+
+impl Debug for IfExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "if {:?} {{\n{}\n}}",
+            self.cond,
+            join_list(&self.then_body, "\n")
+        )?;
+        if let Some(else_body) = &self.else_body {
+            write!(f, " else {{\n{}\n}}", join_list(else_body, "\n"))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use std::fmt::Debug;
+
+use crate::ast::types::Type;
+
+use super::{Expr, ExprKind, ExprTrait};
+
+#[derive(Clone)]
+pub struct IndexExpr {
+    pub postfix_expr: Expr,
+    pub index: Expr,
+    pub r#type: Option<Type>,
+}
+
+impl IndexExpr {
+    pub fn new(postfix_expr: Expr, index: Expr) -> Self {
+        Self {
+            postfix_expr,
+            index,
+            r#type: None,
+        }
+    }
+}
+
+impl ExprTrait for IndexExpr {
+    fn kind(&self) -> ExprKind {
+        ExprKind::Index
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn ExprTrait> {
+        Box::new(self.clone())
+    }
+
+    fn r#type(&self) -> &Option<Type> {
+        &self.r#type
+    }
+}
+
+impl Debug for IndexExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}[{:?}]", self.postfix_expr, self.index)
+    }
+}
@@ -0,0 +1,80 @@
+use std::fmt::Debug;
+
+use crate::{
+    ast::{pattern::Pattern, types::Type},
+    utils::join_list,
+};
+
+use super::{Expr, ExprKind, ExprTrait};
+
+#[derive(Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub body: Expr,
+}
+
+impl MatchArm {
+    pub fn new(pattern: Pattern, guard: Option<Expr>, body: Expr) -> Self {
+        Self {
+            pattern,
+            guard,
+            body,
+        }
+    }
+}
+
+impl Debug for MatchArm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.pattern)?;
+        if let Some(guard) = &self.guard {
+            write!(f, " if {:?}", guard)?;
+        }
+        write!(f, " => {:?}", self.body)
+    }
+}
+
+#[derive(Clone)]
+pub struct MatchExpr {
+    pub expr: Expr,
+    pub arms: Vec<MatchArm>,
+    pub r#type: Option<Type>,
+}
+
+impl MatchExpr {
+    pub fn new(expr: Expr, arms: Vec<MatchArm>) -> Self {
+        Self {
+            expr,
+            arms,
+            r#type: None,
+        }
+    }
+}
+
+impl ExprTrait for MatchExpr {
+    fn kind(&self) -> ExprKind {
+        ExprKind::Match
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn ExprTrait> {
+        Box::new(self.clone())
+    }
+
+    fn r#type(&self) -> &Option<Type> {
+        &self.r#type
+    }
+}
+
+impl Debug for MatchExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "match {:?} {{\n{}\n}}", self.expr, join_list(&self.arms, "\n"))
+    }
+}
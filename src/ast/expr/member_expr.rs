@@ -4,10 +4,22 @@ use crate::ast::{ident::Ident, types::Type};
 
 use super::{Expr, ExprKind, ExprTrait};
 
+#[derive(Clone, Debug)]
+pub enum MemberKind {
+    Field(Vec<u32>),
+    Static,
+    Method(Vec<u32>),
+    // Method of an interface value, called through its vtable
+    Interface(u32),
+    Variant(u32),
+    Len,
+}
+
 #[derive(Clone)]
 pub struct MemberExpr {
     pub postfix_expr: Expr,
     pub member: Ident,
+    pub member_kind: Option<MemberKind>,
     pub r#type: Option<Type>,
 }
 
@@ -16,6 +28,7 @@ impl MemberExpr {
         Self {
             postfix_expr,
             member,
+            member_kind: None,
             r#type: None
         }
     }
@@ -1,28 +1,76 @@
+mod array_expr;
+mod array_repeat_expr;
+mod binary_expr;
+mod bool_literal_expr;
 mod call_expr;
 mod composite_expr;
+mod dyn_expr;
+mod func_expr;
 mod ident_expr;
+mod if_expr;
+mod index_expr;
 mod int_literal_expr;
+mod match_expr;
 mod member_expr;
+mod null_literal_expr;
+mod slice_expr;
 mod str_literal_expr;
+mod try_expr;
+mod tuple_expr;
+mod unwrap_expr;
+mod upcast_expr;
+mod wrap_expr;
 
 use std::{any::Any, fmt::Debug};
 
+pub use array_expr::*;
+pub use array_repeat_expr::*;
+pub use binary_expr::*;
+pub use bool_literal_expr::*;
 pub use call_expr::*;
 pub use composite_expr::*;
+pub use dyn_expr::*;
+pub use func_expr::*;
 pub use ident_expr::*;
+pub use if_expr::*;
+pub use index_expr::*;
 pub use int_literal_expr::*;
+pub use match_expr::*;
 pub use member_expr::*;
+pub use null_literal_expr::*;
+pub use slice_expr::*;
 pub use str_literal_expr::*;
+pub use try_expr::*;
+pub use tuple_expr::*;
+pub use unwrap_expr::*;
+pub use upcast_expr::*;
+pub use wrap_expr::*;
 
 use super::types::Type;
 
 pub enum ExprKind {
+    Array,
+    ArrayRepeat,
+    Binary,
+    BoolLiteral,
     Call,
     Composite,
+    Dyn,
+    Func,
     Ident,
+    If,
+    Index,
     IntLiteral,
+    Match,
+    NullLiteral,
     StrLiteral,
     Member,
+    Slice,
+    Try,
+    Tuple,
+    Unwrap,
+    Upcast,
+    Wrap,
 }
 
 pub trait ExprTrait: Debug {
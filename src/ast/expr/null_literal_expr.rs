@@ -0,0 +1,44 @@
+use std::{any::Any, fmt::Debug};
+
+use crate::ast::types::Type;
+
+use super::{ExprKind, ExprTrait};
+
+#[derive(Clone)]
+pub struct NullLiteralExpr {
+    pub r#type: Option<Type>,
+}
+
+impl NullLiteralExpr {
+    pub fn new() -> Self {
+        Self { r#type: None }
+    }
+}
+
+impl ExprTrait for NullLiteralExpr {
+    fn kind(&self) -> ExprKind {
+        ExprKind::NullLiteral
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn ExprTrait> {
+        Box::new(self.clone())
+    }
+
+    fn r#type(&self) -> &Option<Type> {
+        &self.r#type
+    }
+}
+
+impl Debug for NullLiteralExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "null")
+    }
+}
@@ -0,0 +1,63 @@
+use std::fmt::Debug;
+
+use crate::ast::types::Type;
+
+use super::{Expr, ExprKind, ExprTrait};
+
+#[derive(Clone)]
+pub struct SliceExpr {
+    pub postfix_expr: Expr,
+    pub lo: Option<Expr>,
+    pub hi: Option<Expr>,
+    // The array is copied to the heap when the slice outlives the function creating it
+    pub is_escaping: bool,
+    pub r#type: Option<Type>,
+}
+
+impl SliceExpr {
+    pub fn new(postfix_expr: Expr, lo: Option<Expr>, hi: Option<Expr>) -> Self {
+        Self {
+            postfix_expr,
+            lo,
+            hi,
+            is_escaping: false,
+            r#type: None,
+        }
+    }
+}
+
+impl ExprTrait for SliceExpr {
+    fn kind(&self) -> ExprKind {
+        ExprKind::Slice
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn ExprTrait> {
+        Box::new(self.clone())
+    }
+
+    fn r#type(&self) -> &Option<Type> {
+        &self.r#type
+    }
+}
+
+impl Debug for SliceExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}[", self.postfix_expr)?;
+        if let Some(lo) = &self.lo {
+            write!(f, "{:?}", lo)?;
+        }
+        write!(f, "..")?;
+        if let Some(hi) = &self.hi {
+            write!(f, "{:?}", hi)?;
+        }
+        write!(f, "]")
+    }
+}
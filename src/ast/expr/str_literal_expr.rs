@@ -1,11 +1,8 @@
 use std::{any::Any, fmt::Debug};
 
-use crate::ast::{
-    ident::Ident,
-    types::{RefType, Type},
-};
+use crate::ast::types::{IntType, PtrType, Type};
 
-use super::{ExprKind, ExprTrait, IdentExpr};
+use super::{ExprKind, ExprTrait};
 
 #[derive(Clone)]
 pub struct StrLiteralExpr {
@@ -17,9 +14,7 @@ impl StrLiteralExpr {
     pub fn new(value: String) -> Self {
         Self {
             value,
-            r#type: Some(
-                RefType::new(IdentExpr::new(Ident::new("String".to_string())).into()).into(),
-            ),
+            r#type: Some(PtrType::new(IntType::I8.into()).into()),
         }
     }
 }
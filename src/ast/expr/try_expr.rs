@@ -0,0 +1,54 @@
+use std::{any::Any, fmt::Debug};
+
+use crate::ast::types::Type;
+
+use super::{Expr, ExprKind, ExprTrait};
+
+// `expr?` yields the value of an `Ok` result and returns an `Err` result from the
+// enclosing function
+#[derive(Clone)]
+pub struct TryExpr {
+    pub expr: Expr,
+    pub err_type: Option<Type>,
+    pub return_type: Option<Type>,
+    pub r#type: Option<Type>,
+}
+
+impl TryExpr {
+    pub fn new(expr: Expr) -> Self {
+        Self {
+            expr,
+            err_type: None,
+            return_type: None,
+            r#type: None,
+        }
+    }
+}
+
+impl ExprTrait for TryExpr {
+    fn kind(&self) -> ExprKind {
+        ExprKind::Try
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn ExprTrait> {
+        Box::new(self.clone())
+    }
+
+    fn r#type(&self) -> &Option<Type> {
+        &self.r#type
+    }
+}
+
+impl Debug for TryExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}?", self.expr)
+    }
+}
@@ -0,0 +1,52 @@
+use std::fmt::Debug;
+
+use crate::{ast::types::Type, utils::join_list};
+
+use super::{Expr, ExprKind, ExprTrait};
+
+#[derive(Clone)]
+pub struct TupleExpr {
+    pub elems: Vec<Expr>,
+    pub r#type: Option<Type>,
+}
+
+impl TupleExpr {
+    pub fn new(elems: Vec<Expr>) -> Self {
+        Self {
+            elems,
+            r#type: None,
+        }
+    }
+}
+
+impl ExprTrait for TupleExpr {
+    fn kind(&self) -> ExprKind {
+        ExprKind::Tuple
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn ExprTrait> {
+        Box::new(self.clone())
+    }
+
+    fn r#type(&self) -> &Option<Type> {
+        &self.r#type
+    }
+}
+
+impl Debug for TupleExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.elems.len() == 1 {
+            write!(f, "({:?},)", self.elems[0])
+        } else {
+            write!(f, "({})", join_list(&self.elems, ", "))
+        }
+    }
+}
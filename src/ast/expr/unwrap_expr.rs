@@ -0,0 +1,49 @@
+use std::{any::Any, fmt::Debug};
+
+use crate::ast::types::Type;
+
+use super::{Expr, ExprKind, ExprTrait};
+
+// An optional value that is known not to be null, e.g. after a null check
+#[derive(Clone)]
+pub struct UnwrapExpr {
+    pub expr: Expr,
+    pub r#type: Option<Type>,
+}
+
+impl UnwrapExpr {
+    pub fn new(expr: Expr, r#type: Type) -> Self {
+        Self {
+            expr,
+            r#type: Some(r#type),
+        }
+    }
+}
+
+impl ExprTrait for UnwrapExpr {
+    fn kind(&self) -> ExprKind {
+        ExprKind::Unwrap
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn ExprTrait> {
+        Box::new(self.clone())
+    }
+
+    fn r#type(&self) -> &Option<Type> {
+        &self.r#type
+    }
+}
+
+impl Debug for UnwrapExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.expr)
+    }
+}
@@ -0,0 +1,55 @@
+use std::{any::Any, fmt::Debug};
+
+use crate::ast::types::Type;
+
+use super::{Expr, ExprKind, ExprTrait};
+
+#[derive(Clone)]
+pub struct UpcastExpr {
+    pub expr: Expr,
+    pub path: Vec<u32>,
+    pub r#type: Option<Type>,
+}
+
+impl UpcastExpr {
+    pub fn new(expr: Expr, path: Vec<u32>, r#type: Type) -> Self {
+        Self {
+            expr,
+            path,
+            r#type: Some(r#type),
+        }
+    }
+}
+
+impl ExprTrait for UpcastExpr {
+    fn kind(&self) -> ExprKind {
+        ExprKind::Upcast
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn ExprTrait> {
+        Box::new(self.clone())
+    }
+
+    fn r#type(&self) -> &Option<Type> {
+        &self.r#type
+    }
+}
+
+impl Debug for UpcastExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "({:?} as {:?})",
+            self.expr,
+            self.r#type.as_ref().unwrap()
+        )
+    }
+}
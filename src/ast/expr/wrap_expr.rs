@@ -0,0 +1,54 @@
+use std::{any::Any, fmt::Debug};
+
+use crate::ast::types::Type;
+
+use super::{Expr, ExprKind, ExprTrait};
+
+// A value implicitly converted to an optional type
+#[derive(Clone)]
+pub struct WrapExpr {
+    pub expr: Expr,
+    pub r#type: Option<Type>,
+}
+
+impl WrapExpr {
+    pub fn new(expr: Expr, r#type: Type) -> Self {
+        Self {
+            expr,
+            r#type: Some(r#type),
+        }
+    }
+}
+
+impl ExprTrait for WrapExpr {
+    fn kind(&self) -> ExprKind {
+        ExprKind::Wrap
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn ExprTrait> {
+        Box::new(self.clone())
+    }
+
+    fn r#type(&self) -> &Option<Type> {
+        &self.r#type
+    }
+}
+
+impl Debug for WrapExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "({:?} as {:?})",
+            self.expr,
+            self.r#type.as_ref().unwrap()
+        )
+    }
+}
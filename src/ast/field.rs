@@ -0,0 +1,34 @@
+use std::fmt::Debug;
+
+use super::types::Type;
+
+#[derive(Clone)]
+pub struct Field {
+    pub name: String,
+    pub r#type: Type,
+    pub is_pub: bool,
+    pub is_inherit: bool,
+}
+
+impl Field {
+    pub fn new(name: String, r#type: Type, is_pub: bool, is_inherit: bool) -> Self {
+        Self {
+            name,
+            r#type,
+            is_pub,
+            is_inherit,
+        }
+    }
+}
+
+impl Debug for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_pub {
+            write!(f, "pub ")?;
+        }
+        if self.is_inherit {
+            write!(f, "inherit ")?;
+        }
+        write!(f, "{}: {:?}", self.name, self.r#type)
+    }
+}
@@ -1,8 +1,12 @@
 pub mod arg;
 pub mod decl;
 pub mod expr;
+pub mod field;
 pub mod ident;
 pub mod module_ast;
 pub mod param;
+pub mod pattern;
 pub mod stmt;
+pub mod type_param;
 pub mod types;
+pub mod variant;
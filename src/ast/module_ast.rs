@@ -2,16 +2,29 @@ use std::fmt::Debug;
 
 use crate::utils::join_list;
 
-use super::stmt::Stmt;
+use super::stmt::{Stmt, TypeStmt};
 
 pub struct ModuleAST {
     pub name: String,
     pub stmts: Vec<Stmt>,
+    // Whether the top-level code can run to completion, set by the checker
+    pub is_end_reachable: bool,
+    // Types declared in functions and blocks, set by the checker so that they can be
+    // declared with the top-level types
+    pub local_types: Vec<TypeStmt>,
+    // Modules imported by this one, whose statements the checker moves into it
+    pub imports: Vec<ModuleAST>,
 }
 
 impl ModuleAST {
     pub fn new(name: String, stmts: Vec<Stmt>) -> Self {
-        Self { name, stmts }
+        Self {
+            name,
+            stmts,
+            is_end_reachable: true,
+            local_types: Vec::new(),
+            imports: Vec::new(),
+        }
     }
 }
 
@@ -6,16 +6,28 @@ use super::types::Type;
 pub struct Param {
     pub name: String,
     pub r#type: Type,
+    // Storage whose address outlives the function is allocated on the heap
+    pub is_escaping: bool,
+    pub value_id: Option<u64>,
 }
 
 impl Param {
     pub fn new(name: String, r#type: Type) -> Self {
-        Self { name, r#type }
+        Self {
+            name,
+            r#type,
+            is_escaping: false,
+            value_id: None,
+        }
     }
 }
 
 impl Debug for Param {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: {:?}", self.name, self.r#type)
+        write!(f, "{}", self.name)?;
+        if let Some(value_id) = self.value_id {
+            write!(f, "({})", value_id)?;
+        }
+        write!(f, ": {:?}", self.r#type)
     }
 }
@@ -0,0 +1,99 @@
+use std::fmt::Debug;
+
+use crate::utils::join_list;
+
+use super::{expr::Expr, ident::Ident, types::Type};
+
+#[derive(Clone)]
+pub enum Pattern {
+    Wildcard,
+    Binding(Ident),
+    IntLiteral(String),
+    BoolLiteral(bool),
+    Variant(VariantPattern),
+    Tuple(TuplePattern),
+    Or(Vec<Pattern>),
+}
+
+impl Pattern {
+    // The values bound by a pattern, set by the checker and sorted so that every
+    // alternative of an or-pattern lists them in the same order
+    pub fn value_ids(&self) -> Vec<u64> {
+        let mut value_ids = match self {
+            Pattern::Binding(ident) => vec![ident.symbol_id.unwrap()],
+            Pattern::Variant(variant_pattern) => variant_pattern
+                .payload
+                .as_deref()
+                .map(Pattern::value_ids)
+                .unwrap_or_default(),
+            Pattern::Tuple(tuple_pattern) => tuple_pattern
+                .elems
+                .iter()
+                .flat_map(Pattern::value_ids)
+                .collect(),
+            // Every alternative binds the same values
+            Pattern::Or(patterns) => patterns[0].value_ids(),
+            _ => Vec::new(),
+        };
+        value_ids.sort();
+        value_ids
+    }
+}
+
+#[derive(Clone)]
+pub struct VariantPattern {
+    // The enum type, possibly qualified as in `Outer.Shape.Circle`
+    pub owner: Expr,
+    pub name: String,
+    pub payload: Option<Box<Pattern>>,
+    pub index: Option<u32>,
+    pub payload_type: Option<Type>,
+}
+
+impl VariantPattern {
+    pub fn new(owner: Expr, name: String, payload: Option<Pattern>) -> Self {
+        Self {
+            owner,
+            name,
+            payload: payload.map(Box::new),
+            index: None,
+            payload_type: None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TuplePattern {
+    pub elems: Vec<Pattern>,
+    pub elem_types: Option<Vec<Type>>,
+}
+
+impl TuplePattern {
+    pub fn new(elems: Vec<Pattern>) -> Self {
+        Self {
+            elems,
+            elem_types: None,
+        }
+    }
+}
+
+impl Debug for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Wildcard => write!(f, "_"),
+            Pattern::Binding(ident) => write!(f, "{:?}", ident),
+            Pattern::IntLiteral(value) => write!(f, "{}", value),
+            Pattern::BoolLiteral(value) => write!(f, "{}", value),
+            Pattern::Variant(variant) => {
+                write!(f, "{:?}.{}", variant.owner, variant.name)?;
+                if let Some(payload) = &variant.payload {
+                    write!(f, "({:?})", payload)?;
+                }
+                Ok(())
+            }
+            Pattern::Tuple(tuple) if tuple.elems.len() == 1 => write!(f, "({:?},)", tuple.elems[0]),
+            Pattern::Tuple(tuple) => write!(f, "({})", join_list(&tuple.elems, ", ")),
+            Pattern::Or(patterns) => write!(f, "{}", join_list(patterns, " | ")),
+        }
+    }
+}
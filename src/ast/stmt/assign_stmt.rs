@@ -0,0 +1,41 @@
+use std::{any::Any, fmt::Debug};
+
+use crate::ast::expr::Expr;
+
+use super::{StmtKind, StmtTrait};
+
+#[derive(Clone)]
+pub struct AssignStmt {
+    pub target: Expr,
+    pub value: Expr,
+}
+
+impl AssignStmt {
+    pub fn new(target: Expr, value: Expr) -> Self {
+        Self { target, value }
+    }
+}
+
+impl StmtTrait for AssignStmt {
+    fn kind(&self) -> StmtKind {
+        StmtKind::Assign
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn StmtTrait> {
+        Box::new(self.clone())
+    }
+}
+
+impl Debug for AssignStmt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} = {:?}", self.target, self.value)
+    }
+}
@@ -4,6 +4,7 @@ use crate::{ast::decl::Decl, utils::join_list};
 
 use super::{StmtKind, StmtTrait};
 
+#[derive(Clone)]
 pub struct DeclStmt {
     pub decls: Vec<Decl>,
     pub is_export: bool,
@@ -27,6 +28,10 @@ impl StmtTrait for DeclStmt {
     fn as_mut_any(&mut self) -> &mut dyn Any {
         self
     }
+
+    fn clone_box(&self) -> Box<dyn StmtTrait> {
+        Box::new(self.clone())
+    }
 }
 
 impl Debug for DeclStmt {
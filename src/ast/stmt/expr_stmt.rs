@@ -4,6 +4,7 @@ use crate::ast::expr::Expr;
 
 use super::{StmtTrait, StmtKind};
 
+#[derive(Clone)]
 pub struct ExprStmt {
     pub expr: Expr
 }
@@ -28,6 +29,10 @@ impl StmtTrait for ExprStmt {
     fn as_mut_any(&mut self) -> &mut dyn Any {
         self
     }
+
+    fn clone_box(&self) -> Box<dyn StmtTrait> {
+        Box::new(self.clone())
+    }
 }
 
 impl Debug for ExprStmt {
@@ -0,0 +1,49 @@
+use std::fmt::Debug;
+
+use crate::{
+    ast::{decl::Decl, ident::Ident},
+    utils::join_list,
+};
+
+use super::{StmtKind, StmtTrait};
+
+#[derive(Clone)]
+pub struct ExtendStmt {
+    pub ident: Ident,
+    pub statics: Vec<Decl>,
+}
+
+impl ExtendStmt {
+    pub fn new(ident: Ident, statics: Vec<Decl>) -> Self {
+        Self { ident, statics }
+    }
+}
+
+impl StmtTrait for ExtendStmt {
+    fn kind(&self) -> StmtKind {
+        StmtKind::Extend
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn StmtTrait> {
+        Box::new(self.clone())
+    }
+}
+
+impl Debug for ExtendStmt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "extend {:?} {{\n{}\n}}",
+            self.ident,
+            join_list(&self.statics, "\n")
+        )
+    }
+}
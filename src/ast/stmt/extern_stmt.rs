@@ -4,6 +4,7 @@ use crate::utils::join_list;
 
 use super::{decl_stmt::DeclStmt, StmtKind, StmtTrait};
 
+#[derive(Clone)]
 pub struct ExternStmt {
     pub decl_stmts: Vec<DeclStmt>,
 }
@@ -28,6 +29,10 @@ impl StmtTrait for ExternStmt {
     fn as_mut_any(&mut self) -> &mut dyn Any {
         self
     }
+
+    fn clone_box(&self) -> Box<dyn StmtTrait> {
+        Box::new(self.clone())
+    }
 }
 
 impl Debug for ExternStmt {
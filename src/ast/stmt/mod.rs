@@ -1,4 +1,6 @@
+mod assign_stmt;
 mod decl_stmt;
+mod extend_stmt;
 mod expr_stmt;
 mod extern_stmt;
 mod return_stmt;
@@ -6,14 +8,18 @@ mod type_stmt;
 
 use std::{fmt::Debug, any::Any};
 
+pub use assign_stmt::*;
 pub use decl_stmt::*;
+pub use extend_stmt::*;
 pub use expr_stmt::*;
 pub use extern_stmt::*;
 pub use return_stmt::*;
 pub use type_stmt::*;
 
 pub enum StmtKind {
+    Assign,
     Decl,
+    Extend,
     Extern,
     Expr,
     Return,
@@ -26,8 +32,17 @@ pub trait StmtTrait : Debug {
     fn as_any(&self) -> &dyn Any;
 
     fn as_mut_any(&mut self) -> &mut dyn Any;
+
+    fn clone_box(&self) -> Box<dyn StmtTrait>;
+}
+
+impl Clone for Box<dyn StmtTrait> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
 }
 
+#[derive(Clone)]
 pub struct Stmt {
     inner: Box<dyn StmtTrait>,
 }
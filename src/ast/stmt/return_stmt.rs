@@ -4,14 +4,18 @@ use crate::ast::expr::Expr;
 
 use super::{StmtTrait, StmtKind};
 
+#[derive(Clone)]
 pub struct ReturnStmt {
-    pub expr: Option<Expr>
+    pub expr: Option<Expr>,
+    // Whether the statement is the trailing expression of a function returning a value
+    pub is_implicit: bool
 }
 
 impl ReturnStmt {
     pub fn new(expr: Option<Expr>) -> Self {
         Self {
-            expr
+            expr,
+            is_implicit: false
         }
     }
 }
@@ -28,6 +32,10 @@ impl StmtTrait for ReturnStmt {
     fn as_mut_any(&mut self) -> &mut dyn Any {
         self
     }
+
+    fn clone_box(&self) -> Box<dyn StmtTrait> {
+        Box::new(self.clone())
+    }
 }
 
 impl Debug for ReturnStmt {
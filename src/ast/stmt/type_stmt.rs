@@ -1,17 +1,29 @@
 use std::fmt::Debug;
 
-use crate::ast::{ident::Ident, types::Type};
+use crate::{
+    ast::{ident::Ident, type_param::TypeParam, types::Type},
+    token::CursorPos,
+    utils::join_list,
+};
 
 use super::{StmtKind, StmtTrait};
 
+#[derive(Clone)]
 pub struct TypeStmt {
     pub ident: Ident,
+    pub type_params: Vec<TypeParam>,
     pub r#type: Type,
+    pub pos: Option<CursorPos>,
 }
 
 impl TypeStmt {
     pub fn new(ident: Ident, r#type: Type) -> Self {
-        Self { ident, r#type }
+        Self {
+            ident,
+            type_params: Vec::new(),
+            r#type,
+            pos: None,
+        }
     }
 }
 
@@ -27,10 +39,18 @@ impl StmtTrait for TypeStmt {
     fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
         self
     }
+
+    fn clone_box(&self) -> Box<dyn StmtTrait> {
+        Box::new(self.clone())
+    }
 }
 
 impl Debug for TypeStmt {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "type {:?} {:?}", self.ident, self.r#type)
+        write!(f, "type {:?}", self.ident)?;
+        if !self.type_params.is_empty() {
+            write!(f, "<{}>", join_list(&self.type_params, ", "))?;
+        }
+        write!(f, " {:?}", self.r#type)
     }
 }
@@ -0,0 +1,27 @@
+use std::fmt::Debug;
+
+use crate::utils::join_list;
+
+use super::{ident::Ident, types::Type};
+
+#[derive(Clone)]
+pub struct TypeParam {
+    pub ident: Ident,
+    pub bounds: Vec<Type>,
+}
+
+impl TypeParam {
+    pub fn new(ident: Ident, bounds: Vec<Type>) -> Self {
+        Self { ident, bounds }
+    }
+}
+
+impl Debug for TypeParam {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.ident)?;
+        if !self.bounds.is_empty() {
+            write!(f, ": {}", join_list(&self.bounds, " + "))?;
+        }
+        Ok(())
+    }
+}
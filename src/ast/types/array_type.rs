@@ -5,13 +5,12 @@ use super::{TypeTrait, TypeKind, Type};
 #[derive(Clone)]
 pub struct ArrayType {
     pub elem_type: Type,
+    pub len: Option<u32>,
 }
 
 impl ArrayType {
-    pub fn new(elem_type: Type) -> Self {
-        Self {
-            elem_type
-        }
+    pub fn new(elem_type: Type, len: Option<u32>) -> Self {
+        Self { elem_type, len }
     }
 }
 
@@ -35,6 +34,9 @@ impl TypeTrait for ArrayType {
 
 impl Debug for ArrayType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}[]", self.elem_type)
+        match self.len {
+            Some(len) => write!(f, "{:?}[{}]", self.elem_type, len),
+            None => write!(f, "{:?}[]", self.elem_type),
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,30 @@
+use std::{any::Any, fmt::Debug};
+
+use super::{TypeKind, TypeTrait};
+
+#[derive(Clone)]
+pub struct BoolType;
+
+impl TypeTrait for BoolType {
+    fn kind(&self) -> TypeKind {
+        TypeKind::Bool
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn TypeTrait> {
+        Box::new(self.clone())
+    }
+}
+
+impl Debug for BoolType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "bool")
+    }
+}
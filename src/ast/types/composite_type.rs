@@ -1,22 +1,44 @@
 use std::fmt::Debug;
 
-use crate::{ast::param::Param, utils::join_list};
+use crate::{
+    ast::{decl::Decl, field::Field, stmt::TypeStmt},
+    utils::join_list,
+};
 
-use super::{TypeTrait, TypeKind};
+use super::{Type, TypeKind, TypeTrait};
 
 #[derive(Clone)]
 pub struct CompositeType {
-    pub fields: Vec<Param>,
+    pub fields: Vec<Field>,
+    pub statics: Vec<Decl>,
+    // Types declared among the statics, named through the composite as `Outer.Inner`
+    pub types: Vec<TypeStmt>,
     pub is_restrict: bool,
+    pub interfaces: Vec<Type>,
 }
 
 impl CompositeType {
-    pub fn new(fields: Vec<Param>, is_restrict: bool) -> Self {
+    pub fn new(fields: Vec<Field>, statics: Vec<Decl>, is_restrict: bool) -> Self {
         Self {
             fields,
-            is_restrict
+            statics,
+            types: Vec::new(),
+            is_restrict,
+            interfaces: Vec::new(),
         }
     }
+
+    pub fn is_restricted(&self) -> bool {
+        self.is_restrict || self.fields.iter().any(|field| !field.is_pub)
+    }
+
+    pub fn field_index(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|field| field.name == name)
+    }
+
+    pub fn get_static(&self, name: &str) -> Option<&Decl> {
+        self.statics.iter().find(|decl| decl.name == name)
+    }
 }
 
 impl TypeTrait for CompositeType {
@@ -42,11 +64,20 @@ impl Debug for CompositeType {
         if self.is_restrict {
             write!(f, "restrict ")?;
         }
-        write!(
-            f,
-            "{{\n{}\n}}",
-            join_list(&self.fields, "\n")
-        )?;
+        if !self.interfaces.is_empty() {
+            write!(f, "implements {} ", join_list(&self.interfaces, ", "))?;
+        }
+        write!(f, "{{\n{}", join_list(&self.fields, "\n"))?;
+        if !self.statics.is_empty() || !self.types.is_empty() {
+            write!(f, "\n;")?;
+        }
+        for type_stmt in &self.types {
+            write!(f, "\n{:?}", type_stmt)?;
+        }
+        if !self.statics.is_empty() {
+            write!(f, "\n{}", join_list(&self.statics, "\n"))?;
+        }
+        write!(f, "\n}}")?;
         Ok(())
     }
-}
\ No newline at end of file
+}
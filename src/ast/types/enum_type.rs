@@ -0,0 +1,44 @@
+use std::fmt::Debug;
+
+use crate::{ast::variant::Variant, utils::join_list};
+
+use super::{TypeKind, TypeTrait};
+
+#[derive(Clone)]
+pub struct EnumType {
+    pub variants: Vec<Variant>,
+}
+
+impl EnumType {
+    pub fn new(variants: Vec<Variant>) -> Self {
+        Self { variants }
+    }
+
+    pub fn variant_index(&self, name: &str) -> Option<usize> {
+        self.variants.iter().position(|variant| variant.name == name)
+    }
+}
+
+impl TypeTrait for EnumType {
+    fn kind(&self) -> TypeKind {
+        TypeKind::Enum
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn TypeTrait> {
+        Box::new(self.clone())
+    }
+}
+
+impl Debug for EnumType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "enum {{\n{}\n}}", join_list(&self.variants, "\n"))
+    }
+}
@@ -9,6 +9,7 @@ pub struct FuncType {
     pub return_type: Type,
     pub params: Vec<Param>,
     pub is_var_args: bool,
+    pub is_extern: bool,
 }
 
 impl FuncType {
@@ -17,6 +18,7 @@ impl FuncType {
             return_type,
             params,
             is_var_args,
+            is_extern: false,
         }
     }
 }
@@ -0,0 +1,44 @@
+use std::fmt::Debug;
+
+use crate::{ast::param::Param, utils::join_list};
+
+use super::{TypeKind, TypeTrait};
+
+#[derive(Clone)]
+pub struct InterfaceType {
+    pub methods: Vec<Param>,
+}
+
+impl InterfaceType {
+    pub fn new(methods: Vec<Param>) -> Self {
+        Self { methods }
+    }
+
+    pub fn method_index(&self, name: &str) -> Option<usize> {
+        self.methods.iter().position(|method| method.name == name)
+    }
+}
+
+impl TypeTrait for InterfaceType {
+    fn kind(&self) -> TypeKind {
+        TypeKind::Interface
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn TypeTrait> {
+        Box::new(self.clone())
+    }
+}
+
+impl Debug for InterfaceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "interface {{\n{}\n}}", join_list(&self.methods, "\n"))
+    }
+}
@@ -1,27 +1,48 @@
 mod array_type;
+mod bool_type;
 mod func_type;
 mod int_type;
 mod ptr_type;
 mod ref_type;
 mod composite_type;
+mod enum_type;
+mod interface_type;
+mod never_type;
+mod optional_type;
+mod tuple_type;
+mod void_type;
 
 use std::{fmt::Debug, any::Any};
 
 pub use array_type::*;
+pub use bool_type::*;
 pub use func_type::*;
 pub use int_type::*;
 pub use ptr_type::*;
 pub use ref_type::*;
 pub use composite_type::*;
+pub use enum_type::*;
+pub use interface_type::*;
+pub use never_type::*;
+pub use optional_type::*;
+pub use tuple_type::*;
+pub use void_type::*;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum TypeKind {
     Array,
+    Bool,
     Int,
     Func,
     Ptr,
     Ref,
     Composite,
+    Enum,
+    Interface,
+    Never,
+    Optional,
+    Tuple,
+    Void,
 }
 
 pub trait TypeTrait: Debug {
@@ -0,0 +1,31 @@
+use std::{any::Any, fmt::Debug};
+
+use super::{TypeKind, TypeTrait};
+
+// The return type of functions that never return, such as `exit`
+#[derive(Clone)]
+pub struct NeverType;
+
+impl TypeTrait for NeverType {
+    fn kind(&self) -> TypeKind {
+        TypeKind::Never
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn TypeTrait> {
+        Box::new(self.clone())
+    }
+}
+
+impl Debug for NeverType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "never")
+    }
+}
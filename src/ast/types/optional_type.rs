@@ -0,0 +1,38 @@
+use std::{any::Any, fmt::Debug};
+
+use super::{Type, TypeKind, TypeTrait};
+
+#[derive(Clone)]
+pub struct OptionalType {
+    pub inner: Type,
+}
+
+impl OptionalType {
+    pub fn new(inner: Type) -> Self {
+        Self { inner }
+    }
+}
+
+impl TypeTrait for OptionalType {
+    fn kind(&self) -> TypeKind {
+        TypeKind::Optional
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn TypeTrait> {
+        Box::new(self.clone())
+    }
+}
+
+impl Debug for OptionalType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}?", self.inner)
+    }
+}
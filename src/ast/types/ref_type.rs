@@ -1,12 +1,13 @@
 use std::{any::Any, fmt::Debug};
 
-use crate::ast::expr::Expr;
+use crate::{ast::expr::Expr, utils::join_list};
 
-use super::{TypeKind, TypeTrait};
+use super::{Type, TypeKind, TypeTrait};
 
 #[derive(Clone)]
 pub struct RefType {
     pub expr: Expr,
+    pub type_args: Vec<Type>,
     pub type_id: Option<u64>,
 }
 
@@ -14,6 +15,7 @@ impl RefType {
     pub fn new(expr: Expr) -> Self {
         Self {
             expr,
+            type_args: Vec::new(),
             type_id: None,
         }
     }
@@ -40,6 +42,9 @@ impl TypeTrait for RefType {
 impl Debug for RefType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self.expr)?;
+        if !self.type_args.is_empty() {
+            write!(f, "<{}>", join_list(&self.type_args, ", "))?;
+        }
         if let Some(type_id) = self.type_id {
             write!(f, "({})", type_id)?;
         }
@@ -0,0 +1,44 @@
+use std::{any::Any, fmt::Debug};
+
+use crate::utils::join_list;
+
+use super::{Type, TypeKind, TypeTrait};
+
+#[derive(Clone)]
+pub struct TupleType {
+    pub elem_types: Vec<Type>,
+}
+
+impl TupleType {
+    pub fn new(elem_types: Vec<Type>) -> Self {
+        Self { elem_types }
+    }
+}
+
+impl TypeTrait for TupleType {
+    fn kind(&self) -> TypeKind {
+        TypeKind::Tuple
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn TypeTrait> {
+        Box::new(self.clone())
+    }
+}
+
+impl Debug for TupleType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.elem_types.len() == 1 {
+            write!(f, "({:?},)", self.elem_types[0])
+        } else {
+            write!(f, "({})", join_list(&self.elem_types, ", "))
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use std::fmt::Debug;
+
+use super::types::Type;
+
+#[derive(Clone)]
+pub struct Variant {
+    pub name: String,
+    pub payload: Option<Type>,
+}
+
+impl Variant {
+    pub fn new(name: String, payload: Option<Type>) -> Self {
+        Self { name, payload }
+    }
+}
+
+impl Debug for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(payload) = &self.payload {
+            write!(f, "({:?})", payload)?;
+        }
+        Ok(())
+    }
+}
@@ -1,25 +1,123 @@
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+    ops::RangeInclusive,
+    path::Path,
+    slice,
+};
+
 use crate::{
     ast::{
         arg::Arg,
         decl::Decl,
-        expr::{CallExpr, Expr, ExprKind, IdentExpr, CompositeExpr},
+        expr::{
+            ArrayExpr, ArrayRepeatExpr, BinaryExpr, BinaryOp, BoolLiteralExpr, CallExpr,
+            CompositeExpr, DynExpr, Expr, ExprKind, FuncExpr, IdentExpr, IfExpr, IndexExpr,
+            IntLiteralExpr, MatchExpr, MemberExpr, MemberKind, NullLiteralExpr, SliceExpr, TryExpr,
+            TupleExpr, UnwrapExpr, UpcastExpr, WrapExpr,
+        },
         ident::Ident,
         module_ast::ModuleAST,
         param::Param,
-        stmt::{DeclStmt, ExprStmt, ExternStmt, Stmt, StmtKind, TypeStmt},
-        types::{ArrayType, FuncType, RefType, Type, TypeKind, CompositeType},
+        pattern::Pattern,
+        stmt::{
+            AssignStmt, DeclStmt, ExprStmt, ExtendStmt, ExternStmt, ReturnStmt, Stmt, StmtKind,
+            TypeStmt,
+        },
+        type_param::TypeParam,
+        types::{
+            ArrayType, BoolType, CompositeType, EnumType, FuncType, IntType, InterfaceType,
+            NeverType, OptionalType, PtrType, RefType, TupleType, Type, TypeKind, VoidType,
+        },
     },
+    prelude::prelude,
     symbol_table::SymbolTable,
+    token::CursorPos,
 };
 
 pub struct Checker {
     symbol_table: SymbolTable,
+    module_name: String,
+    // Names under which the types of the module and of the modules it imports can be
+    // qualified, e.g. `shapes.Point`, and the module each refers to
+    module_namespaces: HashMap<String, String>,
+    // The namespaces of every module checked so far, and the module declaring each generic
+    // function, so that instances are checked as part of the module of their generic
+    namespaces_by_module: HashMap<String, HashMap<String, String>>,
+    generic_modules: HashMap<u64, String>,
+    return_types: Vec<Type>,
+    mutable_values: HashSet<u64>,
+    generic_funcs: HashMap<u64, Decl>,
+    generic_types: HashMap<u64, TypeStmt>,
+    // Instances are keyed by their mangled type arguments, which carry the id of each
+    // nominal type, so that a local type never reuses the instance of a global namesake
+    func_instances: HashMap<(u64, String), (u64, Type)>,
+    type_instances: HashMap<(u64, String), u64>,
+    // Generic type and type arguments of each instantiated type, used for inference
+    instance_args: HashMap<u64, (u64, Vec<Type>)>,
+    // Instances created while checking a statement are emitted before it
+    pending_stmts: Vec<Stmt>,
+    // Immutable optional values that are known not to be null in the current block
+    narrowed_values: Vec<u64>,
+    result_type_id: Option<u64>,
+    // Where each value and type declared in the source was defined
+    definitions: HashMap<u64, CursorPos>,
+    warn_shadowing: bool,
+    // Top-level types whose definitions are checked on first use while collecting
+    pending_types: HashMap<u64, TypeStmt>,
+    collected_types: HashMap<u64, TypeStmt>,
+    // Methods of generic instances created while collecting are checked once every
+    // top-level name is known
+    is_collecting: bool,
+    deferred_instances: Vec<(TypeStmt, Vec<TypeParam>, Vec<Type>)>,
+    unsized_types: Vec<u64>,
+    defining_depth: u32,
+    // Composite types, which can contain nested types, and the nested types of each
+    namespaces: HashSet<u64>,
+    nested_types: HashMap<(u64, String), u64>,
+    // Which values declared without an initialiser are assigned on the current path
+    init_state: InitState,
+    // Values of constants and of immutable globals evaluated at compile time
+    const_values: HashMap<u64, Expr>,
+    // Types declared below the top level, which instances emitted there may refer to
+    local_types: Vec<TypeStmt>,
+    // Which parameters each function checked so far may keep references from once it
+    // returns, including `self`
+    kept_params: HashMap<u64, Vec<bool>>,
 }
 
 impl Checker {
-    pub fn new() -> Self {
+    pub fn new(warn_shadowing: bool) -> Self {
         Self {
             symbol_table: SymbolTable::new(),
+            module_name: String::new(),
+            module_namespaces: HashMap::new(),
+            namespaces_by_module: HashMap::new(),
+            generic_modules: HashMap::new(),
+            return_types: Vec::new(),
+            mutable_values: HashSet::new(),
+            generic_funcs: HashMap::new(),
+            generic_types: HashMap::new(),
+            func_instances: HashMap::new(),
+            type_instances: HashMap::new(),
+            instance_args: HashMap::new(),
+            pending_stmts: Vec::new(),
+            narrowed_values: Vec::new(),
+            result_type_id: None,
+            definitions: HashMap::new(),
+            warn_shadowing,
+            pending_types: HashMap::new(),
+            collected_types: HashMap::new(),
+            is_collecting: false,
+            deferred_instances: Vec::new(),
+            unsized_types: Vec::new(),
+            defining_depth: 0,
+            namespaces: HashSet::new(),
+            nested_types: HashMap::new(),
+            init_state: InitState::default(),
+            const_values: HashMap::new(),
+            local_types: Vec::new(),
+            kept_params: HashMap::new(),
         }
     }
 
@@ -30,18 +128,220 @@ impl Checker {
     // ==================================================
 
     fn check_module(&mut self, module: &mut ModuleAST) {
-        for stmt in &mut module.stmts {
+        let mut prelude = prelude();
+        for stmt in &mut prelude {
             self.check_stmt(stmt);
         }
+        self.result_type_id = self
+            .symbol_table
+            .retrieve_type("Result")
+            .map(|entry| entry.type_id);
+
+        self.check_module_stmts(module);
+        module.stmts.splice(0..0, prelude);
+        module.is_end_reachable = self.check_reachability(&module.stmts);
+        module.local_types = mem::take(&mut self.local_types);
+    }
+
+    // Imported modules are checked before the module importing them, and their statements
+    // are moved in front of its own
+    fn check_module_stmts(&mut self, module: &mut ModuleAST) {
+        let mut imported = Vec::new();
+        let mut module_namespaces = HashMap::new();
+        for mut import in mem::take(&mut module.imports) {
+            self.check_module_stmts(&mut import);
+            imported.append(&mut import.stmts);
+            module_namespaces.insert(module_namespace(&import.name), import.name);
+        }
+
+        module_namespaces.insert(module_namespace(&module.name), module.name.clone());
+        self.namespaces_by_module
+            .insert(module.name.clone(), module_namespaces);
+        self.enter_module(module.name.clone());
+        hoist_nested_types(&mut module.stmts);
+        self.collect_stmts(&mut module.stmts);
+        let instances = mem::take(&mut self.pending_stmts);
+        let mut i = instances.len();
+        module.stmts.splice(0..0, instances);
+
+        while i < module.stmts.len() {
+            // Externs are fully checked while collecting
+            if !matches!(module.stmts[i].kind(), StmtKind::Extern) {
+                self.check_stmt(&mut module.stmts[i]);
+            }
+            let instances = mem::take(&mut self.pending_stmts);
+            let len = instances.len();
+            module.stmts.splice(i..i, instances);
+            i += len + 1;
+        }
+        module.stmts.splice(0..0, imported);
+    }
+
+    // Registers every top-level type and function before any body is checked, so that they
+    // can be used before their definition and refer to each other
+    fn collect_stmts(&mut self, stmts: &mut [Stmt]) {
+        self.is_collecting = true;
+        for stmt in stmts.iter_mut() {
+            if let StmtKind::Type = stmt.kind() {
+                let type_stmt = stmt.cast_mut::<TypeStmt>();
+                self.declare_type_stmt(type_stmt);
+                if type_stmt.type_params.is_empty() {
+                    self.pending_types
+                        .insert(type_stmt.ident.symbol_id.unwrap(), type_stmt.clone());
+                }
+            }
+        }
+
+        for stmt in stmts.iter_mut() {
+            if let StmtKind::Extern = stmt.kind() {
+                self.check_extern_stmt(stmt.cast_mut::<ExternStmt>());
+            }
+        }
+
+        for stmt in stmts.iter_mut() {
+            if let StmtKind::Decl = stmt.kind() {
+                for decl in &mut stmt.cast_mut::<DeclStmt>().decls {
+                    if decl.value.as_ref().is_some_and(is_generic_func) {
+                        self.check_generic_decl(decl);
+                    } else if is_func_decl(decl) {
+                        self.declare_func_decl(decl);
+                    }
+                }
+            }
+        }
+
+        for stmt in stmts.iter_mut() {
+            if let StmtKind::Type = stmt.kind() {
+                let type_stmt = stmt.cast_mut::<TypeStmt>();
+                if type_stmt.type_params.is_empty() {
+                    let type_id = type_stmt.ident.symbol_id.unwrap();
+                    self.define_pending_type(type_id);
+                    *type_stmt = self.collected_types.remove(&type_id).unwrap();
+                }
+            }
+        }
+
+        for stmt in stmts.iter_mut() {
+            if let StmtKind::Extend = stmt.kind() {
+                self.declare_extend_stmt(stmt.cast_mut::<ExtendStmt>());
+            }
+        }
+
+        self.is_collecting = false;
+        self.check_type_sizes();
+        for (mut type_stmt, type_params, type_args) in mem::take(&mut self.deferred_instances) {
+            let type_id = type_stmt.ident.symbol_id.unwrap();
+            let module = self
+                .symbol_table
+                .retrieve_type_by_id(type_id)
+                .unwrap()
+                .module
+                .clone();
+            let outer_module = self.enter_module(module);
+            let suspended = self.symbol_table.suspend_scopes();
+            self.symbol_table.open_scope();
+            self.bind_type_params(&type_stmt.ident.name, &type_params, type_args);
+            self.check_type_members(&mut type_stmt);
+            self.symbol_table.close_scope();
+            self.symbol_table.resume_scopes(suspended);
+            self.enter_module(outer_module);
+            self.pending_stmts.push(type_stmt.into());
+        }
+    }
+
+    // Makes the module current for visibility and qualified names, returning the one that was
+    fn enter_module(&mut self, module_name: String) -> String {
+        self.module_namespaces = self
+            .namespaces_by_module
+            .get(&module_name)
+            .cloned()
+            .unwrap_or_default();
+        mem::replace(&mut self.module_name, module_name)
+    }
+
+    fn define_pending_type(&mut self, type_id: u64) {
+        if let Some(mut type_stmt) = self.pending_types.remove(&type_id) {
+            self.define_type_stmt(&mut type_stmt);
+            self.collected_types.insert(type_id, type_stmt);
+        }
+    }
+
+    fn declare_func_decl(&mut self, decl: &mut Decl) {
+        if let Some(r#type) = &mut decl.r#type {
+            self.check_type(r#type);
+        }
+        let value = decl.value.as_mut().unwrap();
+        self.check_func_signature(value.cast_mut::<FuncExpr>(), None);
+        self.check_initialiser(decl);
+
+        self.check_value_redefinition(&decl.name);
+        let value_id = self
+            .symbol_table
+            .push_value(&decl.name, decl.r#type.clone());
+        decl.value_id = Some(value_id);
+        self.define(value_id, decl.pos);
     }
 
     fn check_stmt(&mut self, stmt: &mut Stmt) {
         match stmt.kind() {
+            StmtKind::Assign => self.check_assign_stmt(stmt.cast_mut::<AssignStmt>()),
             StmtKind::Decl => self.check_decl_stmt(stmt.cast_mut::<DeclStmt>()),
+            StmtKind::Extend => self.check_extend_stmt(stmt.cast_mut::<ExtendStmt>()),
             StmtKind::Extern => self.check_extern_stmt(stmt.cast_mut::<ExternStmt>()),
             StmtKind::Expr => self.check_expr_stmt(stmt.cast_mut::<ExprStmt>()),
+            StmtKind::Return => self.check_return_stmt(stmt.cast_mut::<ReturnStmt>()),
             StmtKind::Type => self.check_type_stmt(stmt.cast_mut::<TypeStmt>()),
-            _ => (),
+        }
+    }
+
+    fn check_assign_stmt(&mut self, assign_stmt: &mut AssignStmt) {
+        // Assigning to a value does not read it, so it may not be initialised yet
+        match assign_stmt.target.kind() {
+            ExprKind::Ident => self.bind_ident_expr(assign_stmt.target.cast_mut::<IdentExpr>()),
+            _ => self.check_expr(&mut assign_stmt.target, &None),
+        }
+        let value_id = match assign_stmt.target.kind() {
+            ExprKind::Ident => assign_stmt.target.cast::<IdentExpr>().ident.symbol_id,
+            ExprKind::Member => {
+                let member_expr = assign_stmt.target.cast::<MemberExpr>();
+                match member_expr.member_kind {
+                    Some(MemberKind::Static) => member_expr.member.symbol_id,
+                    Some(MemberKind::Field(_)) => None,
+                    _ => panic!("Cannot assign to {:?}", assign_stmt.target),
+                }
+            }
+            ExprKind::Index => None,
+            _ => panic!("Cannot assign to {:?}", assign_stmt.target),
+        };
+        if let Some(value_id) = value_id.filter(|value_id| !self.mutable_values.contains(value_id))
+        {
+            // An immutable value declared without an initialiser is assigned exactly once
+            match (
+                self.init_state.unassigned.contains(&value_id),
+                self.init_state.assigned.contains(&value_id),
+            ) {
+                (true, false) => (),
+                (true, true) => panic!(
+                    "Immutable value {:?} may already be initialised",
+                    assign_stmt.target
+                ),
+                (false, true) => panic!(
+                    "Cannot assign twice to immutable value {:?}",
+                    assign_stmt.target
+                ),
+                (false, false) => {
+                    panic!("Cannot assign to immutable value {:?}", assign_stmt.target)
+                }
+            }
+        }
+
+        let r#type = assign_stmt.target.r#type().clone();
+        self.check_expr(&mut assign_stmt.value, &r#type);
+        self.coerce(&mut assign_stmt.value, r#type.as_ref().unwrap());
+        if let Some(value_id) = value_id {
+            if self.init_state.unassigned.remove(&value_id) {
+                self.init_state.assigned.insert(value_id);
+            }
         }
     }
 
@@ -51,153 +351,4107 @@ impl Checker {
         }
     }
 
+    fn check_extend_stmt(&mut self, extend_stmt: &mut ExtendStmt) {
+        if extend_stmt.ident.symbol_id.is_none() {
+            self.declare_extend_stmt(extend_stmt);
+        }
+        for decl in &mut extend_stmt.statics {
+            let value = decl.value.as_mut().unwrap();
+            self.check_func_body(value.cast_mut::<FuncExpr>(), decl.value_id);
+        }
+    }
+
+    fn declare_extend_stmt(&mut self, extend_stmt: &mut ExtendStmt) {
+        let name = &extend_stmt.ident.name;
+        let type_id = self
+            .symbol_table
+            .retrieve_type(name)
+            .unwrap_or_else(|| self.cannot_find_type(name))
+            .type_id;
+        if self.generic_types.contains_key(&type_id) {
+            panic!("Generic type `{}` cannot be extended", name);
+        }
+        let type_id = self.canonical_type_id(type_id);
+        extend_stmt.ident.symbol_id = Some(type_id);
+        if self.resolve_type(type_id).kind() != TypeKind::Composite {
+            panic!(
+                "Only composite types can be extended, `{}` is not one",
+                name
+            );
+        }
+
+        let composite_type = self.resolve_composite_type(type_id);
+        let self_type = self.self_type(&extend_stmt.ident);
+        for decl in &mut extend_stmt.statics {
+            if !decl
+                .value
+                .as_ref()
+                .is_some_and(|value| matches!(value.kind(), ExprKind::Func))
+            {
+                panic!(
+                    "Extension member `{}` of `{}` must be a function",
+                    decl.name, name
+                );
+            }
+            if composite_type.field_index(&decl.name).is_some()
+                || composite_type.get_static(&decl.name).is_some()
+            {
+                panic!(
+                    "Extension method `{}` collides with a member of `{}`",
+                    decl.name, name
+                );
+            }
+            if self
+                .symbol_table
+                .retrieve_extension(type_id, &decl.name)
+                .is_some()
+            {
+                panic!(
+                    "Extension method `{}` of `{}` is already defined",
+                    decl.name, name
+                );
+            }
+            self.check_static_decl(decl, &self_type);
+            self.symbol_table
+                .push_extension(type_id, &self.module_name, decl.clone());
+        }
+
+        self.check_operator_methods(&extend_stmt.ident, &extend_stmt.statics);
+    }
+
     fn check_extern_stmt(&mut self, extern_stmt: &mut ExternStmt) {
         for decl_stmt in &mut extern_stmt.decl_stmts {
+            for decl in &mut decl_stmt.decls {
+                if let Some(r#type) = &mut decl.r#type {
+                    if r#type.kind() == TypeKind::Func {
+                        r#type.cast_mut::<FuncType>().is_extern = true;
+                    }
+                }
+            }
             self.check_decl_stmt(decl_stmt);
+            // Externs are initialised by whatever defines them, and external functions are
+            // assumed not to keep their arguments, as C functions usually do not
+            for decl in &decl_stmt.decls {
+                let value_id = decl.value_id.unwrap();
+                self.init_state.unassigned.remove(&value_id);
+                let r#type = decl.r#type.as_ref().unwrap();
+                if r#type.kind() == TypeKind::Func {
+                    let param_count = r#type.cast::<FuncType>().params.len();
+                    self.kept_params.insert(value_id, vec![false; param_count]);
+                }
+            }
         }
     }
 
     fn check_expr_stmt(&mut self, expr_stmt: &mut ExprStmt) {
-        self.check_expr(&mut expr_stmt.expr, &None);
+        self.check_unused_expr(&mut expr_stmt.expr);
+    }
+
+    // The branches of an if or match whose value is discarded need not agree on a type,
+    // so it is checked against void
+    fn check_unused_expr(&mut self, expr: &mut Expr) {
+        match expr.kind() {
+            ExprKind::If => self.check_if_expr(expr.cast_mut::<IfExpr>(), &Some(VoidType.into())),
+            ExprKind::Match => {
+                self.check_match_expr(expr.cast_mut::<MatchExpr>(), &Some(VoidType.into()))
+            }
+            _ => self.check_expr(expr, &None),
+        }
+    }
+
+    fn check_return_stmt(&mut self, return_stmt: &mut ReturnStmt) {
+        let return_type = self.return_types.last().cloned();
+        if return_type
+            .as_ref()
+            .is_some_and(|r#type| r#type.kind() == TypeKind::Never)
+        {
+            panic!("Cannot return from a function returning never");
+        }
+        let is_void = !return_type
+            .as_ref()
+            .is_some_and(|r#type| self.resolve(r#type).kind() != TypeKind::Void);
+        match &mut return_stmt.expr {
+            Some(expr) => {
+                self.check_expr(expr, &return_type);
+                let Some(return_type) = &return_type else {
+                    return;
+                };
+                // The value of a function ending with e.g. a call to `exit` is never produced
+                if self.expr_diverges(expr) {
+                    return;
+                }
+                if is_void {
+                    panic!("Cannot return {:?} from a function returning void", expr);
+                }
+                // A trailing expression without a value lets the function fall off its end
+                let expr_type = self.resolve(expr.r#type().as_ref().unwrap());
+                if return_stmt.is_implicit && expr_type.kind() == TypeKind::Void {
+                    panic!(
+                        "Function can reach its end after {:?} without returning a value of type {:?}",
+                        expr, return_type
+                    );
+                }
+                if !self.try_coerce(expr, return_type) {
+                    panic!(
+                        "Expected a return value of type {:?}, found {:?} of type {:?}",
+                        return_type,
+                        expr,
+                        expr.r#type().as_ref().unwrap()
+                    );
+                }
+            }
+            None if !is_void => panic!("Missing return value of type {:?}", return_type.unwrap()),
+            None => (),
+        }
     }
 
     fn check_type_stmt(&mut self, type_stmt: &mut TypeStmt) {
-        self.check_type(&mut type_stmt.r#type);
-        let type_id = self.symbol_table.push_type(&type_stmt.ident.name);
+        self.declare_type_stmt(type_stmt);
+        if !type_stmt.type_params.is_empty() {
+            return;
+        }
+        let type_id = type_stmt.ident.symbol_id.unwrap();
+        if self
+            .symbol_table
+            .retrieve_type_by_id(type_id)
+            .is_some_and(|entry| entry.r#type.is_none())
+        {
+            self.define_type_stmt(type_stmt);
+        }
+        self.check_type_members(type_stmt);
+        if !self.symbol_table.is_global_scope() {
+            self.local_types.push(type_stmt.clone());
+        }
+    }
+
+    fn declare_type_stmt(&mut self, type_stmt: &mut TypeStmt) {
+        // Instances of generic types are registered before their definition is checked
+        if type_stmt.ident.symbol_id.is_some() {
+            return;
+        }
+        self.check_type_redefinition(&type_stmt.ident.name);
+        let type_id = self
+            .symbol_table
+            .push_type(&type_stmt.ident.name, &self.module_name);
         type_stmt.ident.symbol_id = Some(type_id);
+        self.define(type_id, type_stmt.pos);
+        if type_stmt.r#type.kind() == TypeKind::Composite {
+            self.namespaces.insert(type_id);
+        }
+        // Hoisted nested types directly follow their owner
+        if let Some((owner, name)) = type_stmt.ident.name.rsplit_once('.') {
+            let owner_id = self.symbol_table.retrieve_type(owner).unwrap().type_id;
+            self.nested_types
+                .insert((owner_id, name.to_owned()), type_id);
+        }
+        if !type_stmt.type_params.is_empty() {
+            self.generic_types.insert(type_id, type_stmt.clone());
+        }
+    }
+
+    fn define_type_stmt(&mut self, type_stmt: &mut TypeStmt) {
+        self.defining_depth += 1;
+        self.check_type(&mut type_stmt.r#type);
+        if type_stmt.r#type.kind() == TypeKind::Composite {
+            let self_type = self.self_type(&type_stmt.ident);
+            let composite_type = type_stmt.r#type.cast_mut::<CompositeType>();
+            for decl in &mut composite_type.statics {
+                self.check_static_decl(decl, &self_type);
+            }
+        }
+        self.symbol_table
+            .define_type(type_stmt.ident.symbol_id.unwrap(), type_stmt.r#type.clone());
+
+        // Sizes are checked once every type the definition mentions is defined
+        self.unsized_types.push(type_stmt.ident.symbol_id.unwrap());
+        self.defining_depth -= 1;
+        if self.defining_depth == 0 && !self.is_collecting {
+            self.check_type_sizes();
+        }
+    }
+
+    fn check_type_members(&mut self, type_stmt: &mut TypeStmt) {
+        if type_stmt.r#type.kind() != TypeKind::Composite {
+            return;
+        }
+        self.check_implementations(type_stmt);
+        let composite_type = type_stmt.r#type.cast_mut::<CompositeType>();
+        self.check_operator_methods(&type_stmt.ident, &composite_type.statics);
+        for decl in &mut composite_type.statics {
+            if let Some(value) = &mut decl.value {
+                if let ExprKind::Func = value.kind() {
+                    self.check_func_body(value.cast_mut::<FuncExpr>(), decl.value_id);
+                }
+            }
+        }
     }
 
     // ==================================================
 
     fn check_decl(&mut self, decl: &mut Decl) {
+        if decl.is_const {
+            self.check_const_decl(decl);
+        }
+        let is_generic = decl.value.as_ref().is_some_and(is_generic_func);
+        // Top-level functions were declared while collecting, only their bodies are left
+        if decl.value_id.is_some() {
+            if !is_generic {
+                let value = decl.value.as_mut().unwrap();
+                self.check_func_body(value.cast_mut::<FuncExpr>(), decl.value_id);
+            }
+            return;
+        }
+        if is_generic {
+            self.check_generic_decl(decl);
+            return;
+        }
+
         if let Some(r#type) = &mut decl.r#type {
             self.check_type(r#type);
         }
 
         if let Some(value) = &mut decl.value {
             self.check_expr(value, &decl.r#type);
-            if decl.r#type.is_none() && value.r#type().is_some() {
-                decl.r#type = value.r#type().clone();
-            }
+            self.check_initialiser(decl);
         }
 
         if decl.r#type.is_none() {
             panic!("Type is not specified")
         }
 
+        if let Some(pattern) = &mut decl.pattern {
+            self.check_decl_pattern(
+                pattern,
+                decl.r#type.as_ref().unwrap(),
+                decl.is_mut,
+                decl.pos,
+            );
+            if decl.value.is_none() {
+                panic!("Destructuring declaration {:?} needs a value", pattern);
+            }
+            return;
+        }
+
+        self.check_value_redefinition(&decl.name);
         let value_id = self
             .symbol_table
             .push_value(&decl.name, decl.r#type.clone());
         decl.value_id = Some(value_id);
+        self.define(value_id, decl.pos);
+        if decl.is_mut {
+            self.mutable_values.insert(value_id);
+        }
+        match &decl.value {
+            Some(_) if decl.is_const || self.return_types.is_empty() => self.fold_initialiser(decl),
+            Some(_) => (),
+            None => {
+                self.init_state.unassigned.insert(value_id);
+            }
+        }
     }
 
-    // ==================================================
-
-    fn check_expr(&mut self, expr: &mut Expr, r#type: &Option<Type>) {
-        match expr.kind() {
-            ExprKind::Call => self.check_call_expr(expr.cast_mut::<CallExpr>()),
-            ExprKind::Ident => self.check_ident_expr(expr.cast_mut::<IdentExpr>()),
-            ExprKind::Member => (),
-            ExprKind::Composite => self.check_composite_expr(expr.cast_mut::<CompositeExpr>(), r#type),
-            _ => (),
+    fn check_initialiser(&self, decl: &mut Decl) {
+        let value = decl.value.as_mut().unwrap();
+        match &decl.r#type {
+            Some(r#type) => {
+                if !self.try_coerce(value, r#type) {
+                    panic!(
+                        "`{}` is declared as {:?}, but is initialised with {:?} of type {:?}",
+                        decl.name,
+                        r#type,
+                        value,
+                        value.r#type().as_ref().unwrap()
+                    );
+                }
+            }
+            None if self.expr_diverges(value) => {
+                panic!(
+                    "`{}` is initialised with {:?}, which never completes",
+                    decl.name, value
+                )
+            }
+            None => decl.r#type = value.r#type().clone(),
         }
     }
 
-    fn check_call_expr(&mut self, call_expr: &mut CallExpr) {
-        self.check_expr(&mut call_expr.postfix_expr, &None);
-        for arg in &mut call_expr.args {
-            self.check_arg(arg);
+    fn check_generic_decl(&mut self, decl: &mut Decl) {
+        if !self.return_types.is_empty() {
+            panic!(
+                "Generic function `{}` must be declared at the top level",
+                decl.name
+            );
+        }
+        if decl.r#type.is_some() {
+            panic!(
+                "Generic function `{}` cannot have a type annotation",
+                decl.name
+            );
+        }
+        if decl.is_mut {
+            panic!("Generic function `{}` cannot be mutable", decl.name);
         }
 
-        if call_expr.r#type.is_none() {
-            let func_type = call_expr
-                .postfix_expr
-                .r#type()
-                .as_ref()
-                .unwrap()
-                .cast::<FuncType>();
-            call_expr.r#type = Some(func_type.return_type.clone());
+        // The body is only checked once it is instantiated with concrete types
+        self.check_value_redefinition(&decl.name);
+        let value_id = self.symbol_table.push_value(&decl.name, None);
+        decl.value_id = Some(value_id);
+        self.define(value_id, decl.pos);
+        self.generic_funcs.insert(value_id, decl.clone());
+        self.generic_modules
+            .insert(value_id, self.module_name.clone());
+    }
+
+    fn check_decl_pattern(
+        &mut self,
+        pattern: &mut Pattern,
+        r#type: &Type,
+        is_mut: bool,
+        pos: Option<CursorPos>,
+    ) {
+        let mut bindings = Vec::new();
+        self.check_pattern(pattern, r#type, &mut bindings);
+        if let Some(witness) = self.find_witness(
+            &[vec![pattern.clone()]],
+            &[Pattern::Wildcard],
+            slice::from_ref(r#type),
+        ) {
+            panic!(
+                "Refutable pattern {:?} in declaration: `{}` is not covered",
+                pattern, witness[0]
+            );
         }
+
+        let binding_ids: HashMap<String, u64> = bindings
+            .into_iter()
+            .map(|(name, r#type)| {
+                self.check_value_redefinition(&name);
+                let value_id = self.symbol_table.push_value(&name, Some(r#type));
+                self.define(value_id, pos);
+                if is_mut {
+                    self.mutable_values.insert(value_id);
+                }
+                (name, value_id)
+            })
+            .collect();
+        bind_pattern_ids(pattern, &binding_ids);
     }
 
-    fn check_ident_expr(&mut self, ident_expr: &mut IdentExpr) {
-        self.check_ident(&mut ident_expr.ident);
+    fn check_static_decl(&mut self, decl: &mut Decl, self_type: &Type) {
+        if decl.is_const {
+            self.check_const_decl(decl);
+        }
+        if let Some(r#type) = &mut decl.r#type {
+            self.check_type(r#type);
+        }
 
-        let entry = self
-            .symbol_table
-            .retrieve_value(&ident_expr.ident.name)
-            .unwrap();
-        if ident_expr.r#type.is_none() && entry.r#type.is_some() {
-            ident_expr.r#type = entry.r#type.clone();
+        if let Some(value) = &mut decl.value {
+            if let ExprKind::Func = value.kind() {
+                self.check_func_signature(value.cast_mut::<FuncExpr>(), Some(self_type));
+            } else {
+                self.check_expr(value, &decl.r#type);
+            }
+            if decl.r#type.is_none() && value.r#type().is_some() {
+                decl.r#type = value.r#type().clone();
+            }
         }
-    }
 
-    fn check_composite_expr(&mut self, composite_expr: &mut CompositeExpr, r#type: &Option<Type>) {
-        if r#type.is_some() {
-            composite_expr.r#type = r#type.clone();
+        if decl.r#type.is_none() {
+            panic!("Type of static member `{}` is not specified", decl.name)
+        }
+
+        decl.value_id = Some(self.symbol_table.new_id());
+        if decl.is_mut {
+            self.mutable_values.insert(decl.value_id.unwrap());
         }
-        for (_, expr) in &mut composite_expr.fields {
-            self.check_expr(expr, &None)
+        if decl
+            .value
+            .as_ref()
+            .is_some_and(|value| !matches!(value.kind(), ExprKind::Func))
+        {
+            self.fold_initialiser(decl);
         }
     }
 
     // ==================================================
 
-    fn check_type(&mut self, r#type: &mut Type) {
-        match r#type.kind() {
-            TypeKind::Ref => self.check_ref_type(r#type.cast_mut::<RefType>()),
-            TypeKind::Array => self.check_array_type(r#type.cast_mut::<ArrayType>()),
-            TypeKind::Func => self.check_func_type(r#type.cast_mut::<FuncType>()),
-            TypeKind::Composite => self.check_composite_type(r#type.cast_mut::<CompositeType>()),
-            _ => (),
+    // Global initialisers are evaluated at compile time when they only consist of literals,
+    // arithmetic, coercions, composite values and references to other constants, so that
+    // they initialise the global directly instead of running in `main`. Constants must be
+    // evaluated this way, and integer overflow is an error in any evaluated expression.
+    // Literals are range-checked wherever they appear, but arithmetic in functions wraps
+    fn fold_initialiser(&mut self, decl: &mut Decl) {
+        if decl.pattern.is_some() {
+            return;
+        }
+        let value = decl.value.as_ref().unwrap();
+        match self.eval_const(value) {
+            Ok(folded) => {
+                if !decl.is_mut {
+                    self.const_values
+                        .insert(decl.value_id.unwrap(), folded.clone());
+                }
+                decl.value = Some(folded);
+                decl.is_const_init = true;
+            }
+            Err(expr) if decl.is_const => panic!(
+                "Constant `{}` must be known at compile time, but {:?} is not",
+                decl.name, expr
+            ),
+            Err(_) => (),
         }
     }
 
-    fn check_ref_type(&mut self, ref_type: &mut RefType) {
-        if ref_type.type_id.is_none() {
-            let entry = match ref_type.expr.kind() {
-                ExprKind::Ident => {
-                    let ident_expr = ref_type.expr.cast_mut::<IdentExpr>();
-                    self.symbol_table
-                        .retrieve_type(&ident_expr.ident.name)
-                        .unwrap()
+    fn check_const_decl(&self, decl: &Decl) {
+        if decl.is_mut {
+            panic!("Constant `{}` cannot be mutable", decl.name);
+        }
+        if let Some(pattern) = &decl.pattern {
+            panic!(
+                "Constant {:?} must be a single name, not a pattern",
+                pattern
+            );
+        }
+        match &decl.value {
+            Some(value) if matches!(value.kind(), ExprKind::Func) => panic!(
+                "Function `{}` is already constant, declare it with `let`",
+                decl.name
+            ),
+            Some(_) => (),
+            None => panic!("Constant `{}` must be initialised", decl.name),
+        }
+    }
+
+    // Evaluates a checked expression to one made only of literals, or returns the part of
+    // it that is only known at runtime
+    fn eval_const(&self, expr: &Expr) -> Result<Expr, Expr> {
+        match expr.kind() {
+            ExprKind::IntLiteral => {
+                let int_literal = expr.cast::<IntLiteralExpr>();
+                let value = int_literal.value.parse().unwrap_or_else(|_| {
+                    panic!("Integer literal {:?} is too large", int_literal.value)
+                });
+                Ok(self.const_int(value, &int_literal.r#type, expr))
+            }
+            ExprKind::BoolLiteral | ExprKind::StrLiteral | ExprKind::NullLiteral => {
+                Ok(expr.clone())
+            }
+            ExprKind::Ident => {
+                let value_id = expr.cast::<IdentExpr>().ident.symbol_id.unwrap();
+                self.const_values
+                    .get(&value_id)
+                    .cloned()
+                    .ok_or_else(|| expr.clone())
+            }
+            ExprKind::Member => self.eval_const_member(expr),
+            ExprKind::Index => {
+                let index_expr = expr.cast::<IndexExpr>();
+                let array = self.eval_const(&index_expr.postfix_expr)?;
+                let index = self.eval_const(&index_expr.index)?;
+                let index: i128 = index.cast::<IntLiteralExpr>().value.parse().unwrap();
+                let elems = const_elems(&array).ok_or_else(|| expr.clone())?;
+                match usize::try_from(index)
+                    .ok()
+                    .filter(|&index| index < elems.len())
+                {
+                    Some(index) => Ok(elems[index].clone()),
+                    None => panic!(
+                        "Index {} is out of bounds in {:?}, the length is {}",
+                        index,
+                        expr,
+                        elems.len()
+                    ),
                 }
-                ExprKind::Member => todo!(),
-                _ => panic!(),
-            };
-            ref_type.type_id = Some(entry.type_id);
+            }
+            ExprKind::Binary => self.eval_const_binary(expr),
+            ExprKind::Composite => {
+                let mut composite_expr = expr.cast::<CompositeExpr>().clone();
+                for (_, field) in &mut composite_expr.fields {
+                    *field = self.eval_const(field)?;
+                }
+                Ok(composite_expr.into())
+            }
+            ExprKind::Array => {
+                let mut array_expr = expr.cast::<ArrayExpr>().clone();
+                for elem in &mut array_expr.elems {
+                    *elem = self.eval_const(elem)?;
+                }
+                Ok(array_expr.into())
+            }
+            ExprKind::ArrayRepeat => {
+                let mut array_repeat_expr = expr.cast::<ArrayRepeatExpr>().clone();
+                array_repeat_expr.value = self.eval_const(&array_repeat_expr.value)?;
+                Ok(array_repeat_expr.into())
+            }
+            ExprKind::Tuple => {
+                let mut tuple_expr = expr.cast::<TupleExpr>().clone();
+                for elem in &mut tuple_expr.elems {
+                    *elem = self.eval_const(elem)?;
+                }
+                Ok(tuple_expr.into())
+            }
+            ExprKind::Wrap => {
+                let mut wrap_expr = expr.cast::<WrapExpr>().clone();
+                wrap_expr.expr = self.eval_const(&wrap_expr.expr)?;
+                Ok(wrap_expr.into())
+            }
+            ExprKind::Unwrap => {
+                let inner = self.eval_const(&expr.cast::<UnwrapExpr>().expr)?;
+                match inner.kind() {
+                    ExprKind::Wrap => Ok(inner.cast::<WrapExpr>().expr.clone()),
+                    _ => Err(expr.clone()),
+                }
+            }
+            ExprKind::Upcast => {
+                let upcast_expr = expr.cast::<UpcastExpr>();
+                if self.is_ptr(&upcast_expr.expr) {
+                    return Err(expr.clone());
+                }
+                let inner = self.eval_const(&upcast_expr.expr)?;
+                Ok(const_field(&inner, &upcast_expr.path))
+            }
+            // A slice refers to the storage of its array, so only slices of literals, which
+            // have no other storage, are constant
+            ExprKind::Slice => {
+                let mut slice_expr = expr.cast::<SliceExpr>().clone();
+                let is_literal = matches!(
+                    slice_expr.postfix_expr.kind(),
+                    ExprKind::Array | ExprKind::ArrayRepeat | ExprKind::StrLiteral
+                );
+                if !is_literal || slice_expr.lo.is_some() || slice_expr.hi.is_some() {
+                    return Err(expr.clone());
+                }
+                slice_expr.postfix_expr = self.eval_const(&slice_expr.postfix_expr)?;
+                Ok(slice_expr.into())
+            }
+            _ => Err(expr.clone()),
         }
     }
 
-    fn check_array_type(&mut self, array_type: &mut ArrayType) {
-        self.check_type(&mut array_type.elem_type);
+    fn eval_const_member(&self, expr: &Expr) -> Result<Expr, Expr> {
+        let member_expr = expr.cast::<MemberExpr>();
+        match member_expr.member_kind.as_ref().unwrap() {
+            MemberKind::Static => {
+                let value_id = member_expr.member.symbol_id.unwrap();
+                self.const_values
+                    .get(&value_id)
+                    .cloned()
+                    .ok_or_else(|| expr.clone())
+            }
+            MemberKind::Field(path) if !self.is_ptr(&member_expr.postfix_expr) => {
+                let owner = self.eval_const(&member_expr.postfix_expr)?;
+                Ok(const_field(&owner, path))
+            }
+            MemberKind::Len if self.is_fixed_array(&member_expr.postfix_expr) => {
+                let array_type = self.resolve(member_expr.postfix_expr.r#type().as_ref().unwrap());
+                let len = array_type.cast::<ArrayType>().len.unwrap();
+                Ok(self.const_int(len.into(), &member_expr.r#type, expr))
+            }
+            _ => Err(expr.clone()),
+        }
     }
 
-    fn check_func_type(&mut self, func_type: &mut FuncType) {
-        self.check_type(&mut func_type.return_type);
-        for param in &mut func_type.params {
-            self.check_param(param);
+    fn eval_const_binary(&self, expr: &Expr) -> Result<Expr, Expr> {
+        let binary_expr = expr.cast::<BinaryExpr>();
+        // Operators of composite values are methods, which only run at runtime
+        if binary_expr.method_id.is_some() {
+            return Err(expr.clone());
+        }
+        let lhs = self.eval_const(&binary_expr.lhs)?;
+        if binary_expr.op == BinaryOp::Coalesce {
+            let is_optional =
+                self.resolve(binary_expr.r#type.as_ref().unwrap()).kind() == TypeKind::Optional;
+            return match lhs.kind() {
+                ExprKind::NullLiteral => self.eval_const(&binary_expr.rhs),
+                _ if is_optional => Ok(lhs),
+                _ => Ok(lhs.cast::<WrapExpr>().expr.clone()),
+            };
         }
+        let rhs = self.eval_const(&binary_expr.rhs)?;
+
+        let (lhs, rhs) = match (lhs.kind(), rhs.kind()) {
+            (ExprKind::IntLiteral, ExprKind::IntLiteral) => (
+                lhs.cast::<IntLiteralExpr>().value.parse::<i128>().unwrap(),
+                rhs.cast::<IntLiteralExpr>().value.parse::<i128>().unwrap(),
+            ),
+            (ExprKind::BoolLiteral, ExprKind::BoolLiteral) => (
+                lhs.cast::<BoolLiteralExpr>().value.into(),
+                rhs.cast::<BoolLiteralExpr>().value.into(),
+            ),
+            _ => return Err(expr.clone()),
+        };
+        // Operands fit in 64 bits, so the exact result fits in 128 bits before it is checked
+        let value = match binary_expr.op {
+            BinaryOp::Add => lhs + rhs,
+            BinaryOp::Sub => lhs - rhs,
+            BinaryOp::Mul => lhs * rhs,
+            BinaryOp::Div if rhs == 0 => {
+                panic!("Division by zero in constant expression {:?}", expr)
+            }
+            BinaryOp::Div => lhs / rhs,
+            BinaryOp::Eq => return Ok(BoolLiteralExpr::new(lhs == rhs).into()),
+            BinaryOp::Ne => return Ok(BoolLiteralExpr::new(lhs != rhs).into()),
+            BinaryOp::Lt => return Ok(BoolLiteralExpr::new(lhs < rhs).into()),
+            BinaryOp::Gt => return Ok(BoolLiteralExpr::new(lhs > rhs).into()),
+            BinaryOp::Le => return Ok(BoolLiteralExpr::new(lhs <= rhs).into()),
+            BinaryOp::Ge => return Ok(BoolLiteralExpr::new(lhs >= rhs).into()),
+            BinaryOp::Coalesce => unreachable!(),
+        };
+        Ok(self.const_int(value, &binary_expr.r#type, expr))
     }
 
-    fn check_composite_type(&mut self, composite_type: &mut CompositeType) {
-        for field in &mut composite_type.fields {
-            self.check_type(&mut field.r#type);
+    fn const_int(&self, value: i128, r#type: &Option<Type>, expr: &Expr) -> Expr {
+        let int_type = self.resolve(r#type.as_ref().unwrap());
+        if !int_range(int_type.cast::<IntType>()).contains(&value) {
+            panic!(
+                "Integer overflow in constant expression {:?}, {} does not fit in {:?}",
+                expr,
+                value,
+                r#type.as_ref().unwrap()
+            );
         }
+        let mut int_literal = IntLiteralExpr::new(value.to_string());
+        int_literal.r#type = r#type.clone();
+        int_literal.into()
     }
 
     // ==================================================
 
-    fn check_ident(&mut self, ident: &mut Ident) {
-        let decl_entry = self.symbol_table.retrieve_value(&ident.name).unwrap();
-        ident.symbol_id = Some(decl_entry.value_id);
-    }
+    fn check_expr(&mut self, expr: &mut Expr, r#type: &Option<Type>) {
+        // Literals expected to be optional are checked against the inner type and wrapped
+        // when they are coerced
+        let inner_type = match expr.kind() {
+            ExprKind::IntLiteral
+            | ExprKind::Composite
+            | ExprKind::Array
+            | ExprKind::ArrayRepeat
+            | ExprKind::Tuple => self.optional_inner(r#type),
+            _ => None,
+        };
+        let r#type = match &inner_type {
+            Some(_) => &inner_type,
+            None => r#type,
+        };
 
-    fn check_arg(&mut self, arg: &mut Arg) {
-        self.check_expr(&mut arg.expr, &None);
+        match expr.kind() {
+            ExprKind::Call => self.check_call_expr(expr.cast_mut::<CallExpr>(), r#type),
+            ExprKind::Ident => {
+                self.check_ident_expr(expr.cast_mut::<IdentExpr>());
+                self.narrow(expr);
+            }
+            ExprKind::Member => {
+                self.check_member_expr(expr.cast_mut::<MemberExpr>(), false, r#type)
+            }
+            ExprKind::Composite => {
+                self.check_composite_expr(expr.cast_mut::<CompositeExpr>(), r#type)
+            }
+            ExprKind::Func => self.check_func_expr(expr.cast_mut::<FuncExpr>()),
+            ExprKind::Match => self.check_match_expr(expr.cast_mut::<MatchExpr>(), r#type),
+            ExprKind::If => self.check_if_expr(expr.cast_mut::<IfExpr>(), r#type),
+            ExprKind::Try => self.check_try_expr(expr.cast_mut::<TryExpr>()),
+            ExprKind::Array => self.check_array_expr(expr.cast_mut::<ArrayExpr>(), r#type),
+            ExprKind::ArrayRepeat => {
+                self.check_array_repeat_expr(expr.cast_mut::<ArrayRepeatExpr>(), r#type)
+            }
+            ExprKind::Index => self.check_index_expr(expr.cast_mut::<IndexExpr>()),
+            ExprKind::Binary => self.check_binary_expr(expr.cast_mut::<BinaryExpr>(), r#type),
+            ExprKind::Slice => self.check_slice_expr(expr.cast_mut::<SliceExpr>()),
+            ExprKind::Tuple => self.check_tuple_expr(expr.cast_mut::<TupleExpr>(), r#type),
+            ExprKind::IntLiteral => {
+                self.check_int_literal_expr(expr.cast_mut::<IntLiteralExpr>(), r#type)
+            }
+            ExprKind::NullLiteral => {
+                self.check_null_literal_expr(expr.cast_mut::<NullLiteralExpr>(), r#type)
+            }
+            _ => (),
+        }
     }
 
-    fn check_param(&mut self, param: &mut Param) {
-        self.check_type(&mut param.r#type);
-    }
+    fn check_call_expr(&mut self, call_expr: &mut CallExpr, r#type: &Option<Type>) {
+        let mut checked_args = vec![false; call_expr.args.len()];
+        if let Some(generic_id) = self.generic_callee(&call_expr.postfix_expr) {
+            checked_args = self.check_generic_callee(call_expr, generic_id);
+        } else if let ExprKind::Member = call_expr.postfix_expr.kind() {
+            self.check_member_expr(
+                call_expr.postfix_expr.cast_mut::<MemberExpr>(),
+                true,
+                r#type,
+            );
+        } else {
+            self.check_expr(&mut call_expr.postfix_expr, &None);
+        }
+
+        let callee_type = call_expr.postfix_expr.r#type().as_ref().unwrap();
+        let resolved_type = self.resolve(callee_type);
+        if resolved_type.kind() != TypeKind::Func {
+            panic!(
+                "{:?} of type {:?} is not callable",
+                call_expr.postfix_expr, callee_type
+            );
+        }
+        let func_type = resolved_type.cast::<FuncType>().clone();
+        let mut params = func_type.params.iter();
+        if is_method_call(&call_expr.postfix_expr) {
+            params.next();
+        }
+        if func_type.is_var_args {
+            params = params.as_slice()[..params.len().saturating_sub(1)].iter();
+        }
+        let arity = params.len();
+        let arg_count = call_expr.args.len();
+        if func_type.is_var_args && arg_count < arity {
+            panic!(
+                "{:?} takes at least {} argument{}, but {} were supplied",
+                call_expr.postfix_expr,
+                arity,
+                if arity == 1 { "" } else { "s" },
+                arg_count
+            );
+        }
+        if !func_type.is_var_args && arg_count != arity {
+            panic!(
+                "{:?} takes {} argument{}, but {} were supplied",
+                call_expr.postfix_expr,
+                arity,
+                if arity == 1 { "" } else { "s" },
+                arg_count
+            );
+        }
+        let var_args_type = match func_type.params.last() {
+            Some(param) if func_type.is_var_args && !func_type.is_extern => {
+                Some(self.resolve(&param.r#type).cast::<ArrayType>().clone())
+            }
+            _ => None,
+        };
+        for (i, arg) in call_expr.args.iter_mut().enumerate() {
+            let r#type = match params.as_slice().get(i) {
+                Some(param) => Some(param.r#type.clone()),
+                None => var_args_type
+                    .as_ref()
+                    .map(|r#type| r#type.elem_type.clone()),
+            };
+            if !checked_args.get(i).is_some_and(|checked| *checked) {
+                self.check_arg(arg, &r#type);
+            }
+            match &r#type {
+                Some(r#type) => {
+                    if !self.try_coerce(&mut arg.expr, r#type) {
+                        panic!(
+                            "Argument {} of {:?} has type {:?}, found {:?} of type {:?}",
+                            i + 1,
+                            call_expr.postfix_expr,
+                            r#type,
+                            arg.expr,
+                            arg.expr.r#type().as_ref().unwrap()
+                        );
+                    }
+                }
+                // Extra arguments of C variadic functions are passed as they are
+                None => {
+                    let arg_type = self.resolve(arg.expr.r#type().as_ref().unwrap());
+                    let is_scalar = match arg_type.kind() {
+                        TypeKind::Int | TypeKind::Bool | TypeKind::Ptr => true,
+                        TypeKind::Optional => {
+                            arg_type.cast::<OptionalType>().inner.kind() == TypeKind::Ptr
+                        }
+                        _ => false,
+                    };
+                    if !is_scalar {
+                        panic!(
+                            "{:?} of type {:?} cannot be passed to C variadic function {:?}",
+                            arg.expr,
+                            arg.expr.r#type().as_ref().unwrap(),
+                            call_expr.postfix_expr
+                        );
+                    }
+                }
+            }
+        }
+
+        // Native variadic functions receive their extra arguments packed into a slice
+        if let Some(var_args_type) = var_args_type {
+            let var_args = call_expr
+                .args
+                .split_off(params.len().min(call_expr.args.len()));
+            let len = var_args.len() as u32;
+            let mut array_expr = ArrayExpr::new(var_args.into_iter().map(|arg| arg.expr).collect());
+            array_expr.r#type =
+                Some(ArrayType::new(var_args_type.elem_type.clone(), Some(len)).into());
+            let mut slice_expr = SliceExpr::new(array_expr.into(), None, None);
+            slice_expr.r#type = Some(var_args_type.into());
+            call_expr.args.push(Arg::new(slice_expr.into()));
+        }
+
+        if call_expr.r#type.is_none() {
+            call_expr.r#type = Some(func_type.return_type.clone());
+        }
+    }
+
+    fn generic_callee(&self, expr: &Expr) -> Option<u64> {
+        let ExprKind::Ident = expr.kind() else {
+            return None;
+        };
+        self.generic_func_id(&expr.cast::<IdentExpr>().ident.name)
+    }
+
+    fn generic_func_id(&self, name: &str) -> Option<u64> {
+        let entry = self.symbol_table.retrieve_value(name)?;
+        self.generic_funcs
+            .contains_key(&entry.value_id)
+            .then_some(entry.value_id)
+    }
+
+    // Infers the type arguments of a generic callee from the arguments mentioning type
+    // parameters, which are checked here, and points the callee at the matching instance
+    fn check_generic_callee(&mut self, call_expr: &mut CallExpr, generic_id: u64) -> Vec<bool> {
+        let decl = self.generic_funcs[&generic_id].clone();
+        let func_expr = decl.value.as_ref().unwrap().cast::<FuncExpr>();
+        let type_params: Vec<&str> = func_expr
+            .type_params
+            .iter()
+            .map(|type_param| type_param.ident.name.as_str())
+            .collect();
+        let params = match func_expr.is_var_args {
+            true => &func_expr.params[..func_expr.params.len() - 1],
+            false => &func_expr.params[..],
+        };
+
+        let mut bindings = HashMap::new();
+        let mut checked_args = vec![false; call_expr.args.len()];
+        for (i, (arg, param)) in call_expr.args.iter_mut().zip(params).enumerate() {
+            if !mentions_type_params(&param.r#type, &type_params) {
+                continue;
+            }
+            self.check_arg(arg, &None);
+            checked_args[i] = true;
+            let arg_type = arg.expr.r#type().clone().unwrap();
+            self.infer_type_args(&param.r#type, &arg_type, &type_params, &mut bindings);
+        }
+
+        let type_args = type_params
+            .iter()
+            .map(|name| {
+                bindings.remove(name).unwrap_or_else(|| {
+                    panic!(
+                        "Cannot infer type parameter `{}` of `{}` from the arguments",
+                        name, decl.name
+                    )
+                })
+            })
+            .collect();
+        let (value_id, r#type) = self.instantiate_func(generic_id, type_args);
+        let ident_expr = call_expr.postfix_expr.cast_mut::<IdentExpr>();
+        ident_expr.ident.symbol_id = Some(value_id);
+        ident_expr.r#type = Some(r#type);
+        checked_args
+    }
+
+    fn infer_type_args<'a>(
+        &self,
+        param_type: &Type,
+        arg_type: &Type,
+        type_params: &[&'a str],
+        bindings: &mut HashMap<&'a str, Type>,
+    ) {
+        if param_type.kind() == TypeKind::Ref {
+            let ref_type = param_type.cast::<RefType>();
+            if let Some(name) = type_param_name(ref_type, type_params) {
+                match bindings.get(name) {
+                    Some(bound) if self.mangle_type(bound) != self.mangle_type(arg_type) => panic!(
+                        "Type parameter `{}` is inferred as both {:?} and {:?}",
+                        name, bound, arg_type
+                    ),
+                    Some(_) => (),
+                    None => {
+                        bindings.insert(name, arg_type.clone());
+                    }
+                }
+                return;
+            }
+            if ref_type.type_args.is_empty() || arg_type.kind() != TypeKind::Ref {
+                return;
+            }
+            let arg_id = self.canonical_type_id(arg_type.cast::<RefType>().type_id.unwrap());
+            let Some((generic_id, type_args)) = self.instance_args.get(&arg_id) else {
+                return;
+            };
+            let generic_name = &self.generic_types[generic_id].ident.name;
+            if matches!(ref_type.expr.kind(), ExprKind::Ident)
+                && ref_type.expr.cast::<IdentExpr>().ident.name == *generic_name
+            {
+                for (param_type, arg_type) in ref_type.type_args.iter().zip(type_args) {
+                    self.infer_type_args(param_type, arg_type, type_params, bindings);
+                }
+            }
+            return;
+        }
+
+        let arg_type = self.resolve(arg_type);
+        if param_type.kind() != arg_type.kind() {
+            return;
+        }
+        match param_type.kind() {
+            TypeKind::Ptr => self.infer_type_args(
+                &param_type.cast::<PtrType>().pointee,
+                &arg_type.cast::<PtrType>().pointee,
+                type_params,
+                bindings,
+            ),
+            TypeKind::Array => self.infer_type_args(
+                &param_type.cast::<ArrayType>().elem_type,
+                &arg_type.cast::<ArrayType>().elem_type,
+                type_params,
+                bindings,
+            ),
+            TypeKind::Tuple => {
+                let param_types = &param_type.cast::<TupleType>().elem_types;
+                let arg_types = &arg_type.cast::<TupleType>().elem_types;
+                for (param_type, arg_type) in param_types.iter().zip(arg_types) {
+                    self.infer_type_args(param_type, arg_type, type_params, bindings);
+                }
+            }
+            TypeKind::Func => {
+                let param_func = param_type.cast::<FuncType>();
+                let arg_func = arg_type.cast::<FuncType>();
+                for (param, arg) in param_func.params.iter().zip(&arg_func.params) {
+                    self.infer_type_args(&param.r#type, &arg.r#type, type_params, bindings);
+                }
+                self.infer_type_args(
+                    &param_func.return_type,
+                    &arg_func.return_type,
+                    type_params,
+                    bindings,
+                );
+            }
+            _ => (),
+        }
+    }
+
+    fn check_ident_expr(&mut self, ident_expr: &mut IdentExpr) {
+        self.bind_ident_expr(ident_expr);
+        let value_id = ident_expr.ident.symbol_id.unwrap();
+        if self.init_state.unassigned.contains(&value_id) {
+            match self.init_state.assigned.contains(&value_id) {
+                true => panic!(
+                    "`{}` may be used before it is initialised, it is not assigned on every path",
+                    ident_expr.ident.name
+                ),
+                false => panic!(
+                    "`{}` is used before it is initialised",
+                    ident_expr.ident.name
+                ),
+            }
+        }
+    }
+
+    fn bind_ident_expr(&mut self, ident_expr: &mut IdentExpr) {
+        if self.generic_func_id(&ident_expr.ident.name).is_some() {
+            panic!(
+                "Generic function `{}` must be called so that its type arguments can be inferred",
+                ident_expr.ident.name
+            );
+        }
+        self.check_ident(&mut ident_expr.ident);
+
+        let entry = self
+            .symbol_table
+            .retrieve_value(&ident_expr.ident.name)
+            .unwrap();
+        if ident_expr.r#type.is_none() && entry.r#type.is_some() {
+            ident_expr.r#type = entry.r#type.clone();
+        }
+    }
+
+    fn check_member_expr(
+        &mut self,
+        member_expr: &mut MemberExpr,
+        is_callee: bool,
+        r#type: &Option<Type>,
+    ) {
+        if let Some(type_id) = self.check_static_owner(&mut member_expr.postfix_expr, r#type) {
+            if self.resolve_type(type_id).kind() == TypeKind::Enum {
+                self.check_variant_member(member_expr, type_id, is_callee);
+                return;
+            }
+
+            let decl = self
+                .get_static(type_id, &member_expr.member.name)
+                .unwrap_or_else(|| {
+                    panic!(
+                        "No static member `{}` in {:?}",
+                        member_expr.member.name, member_expr.postfix_expr
+                    )
+                });
+            member_expr.member.symbol_id = decl.value_id;
+            member_expr.member_kind = Some(MemberKind::Static);
+            member_expr.r#type = decl.r#type.clone();
+            return;
+        }
+
+        self.check_expr(&mut member_expr.postfix_expr, &None);
+        let mut owner_type = member_expr.postfix_expr.r#type().clone().unwrap();
+        if owner_type.kind() == TypeKind::Ptr {
+            owner_type = owner_type.cast::<PtrType>().pointee.clone();
+        }
+        if self.resolve(&owner_type).kind() == TypeKind::Array && member_expr.member.name == "len" {
+            member_expr.member_kind = Some(MemberKind::Len);
+            member_expr.r#type = Some(IntType::I64.into());
+            return;
+        }
+        if self.resolve(&owner_type).kind() == TypeKind::Tuple {
+            let tuple_type = self.resolve(&owner_type).cast::<TupleType>().clone();
+            let index: usize = member_expr
+                .member
+                .name
+                .parse()
+                .ok()
+                .filter(|index| *index < tuple_type.elem_types.len())
+                .unwrap_or_else(|| {
+                    panic!(
+                        "No element `{}` in {:?} of type {:?}",
+                        member_expr.member.name, member_expr.postfix_expr, tuple_type
+                    )
+                });
+            member_expr.member_kind = Some(MemberKind::Field(vec![index as u32]));
+            member_expr.r#type = Some(tuple_type.elem_types[index].clone());
+            return;
+        }
+        if self.resolve(&owner_type).kind() == TypeKind::Interface {
+            self.check_interface_member(member_expr, &owner_type, is_callee);
+            return;
+        }
+        if self.resolve(&owner_type).kind() == TypeKind::Optional {
+            panic!(
+                "{:?} may be null, compare it with `null` before accessing `{}`",
+                member_expr.postfix_expr, member_expr.member.name
+            );
+        }
+        if owner_type.kind() != TypeKind::Ref {
+            panic!("{:?} has no members", member_expr.postfix_expr);
+        }
+        let (member_kind, value_id, r#type) = self
+            .lookup_member(
+                owner_type.cast::<RefType>().type_id.unwrap(),
+                &member_expr.member.name,
+                is_callee,
+            )
+            .unwrap_or_else(|| {
+                panic!(
+                    "No member `{}` in {:?}",
+                    member_expr.member.name, member_expr.postfix_expr
+                )
+            });
+        member_expr.member.symbol_id = value_id;
+        member_expr.member_kind = Some(member_kind);
+        member_expr.r#type = Some(r#type);
+    }
+
+    fn check_interface_member(
+        &mut self,
+        member_expr: &mut MemberExpr,
+        owner_type: &Type,
+        is_callee: bool,
+    ) {
+        let r#type = self.resolve(owner_type);
+        let interface_type = r#type.cast::<InterfaceType>();
+        let index = interface_type
+            .method_index(&member_expr.member.name)
+            .unwrap_or_else(|| {
+                panic!(
+                    "No method `{}` in interface {:?}",
+                    member_expr.member.name, owner_type
+                )
+            });
+        if !is_callee {
+            panic!("Method `{}` must be called", member_expr.member.name);
+        }
+
+        // Methods are called through the vtable with the data pointer as `self`
+        let mut func_type = interface_type.methods[index]
+            .r#type
+            .cast::<FuncType>()
+            .clone();
+        func_type.params.insert(
+            0,
+            Param::new("self".to_owned(), PtrType::new(IntType::I8.into()).into()),
+        );
+        member_expr.member_kind = Some(MemberKind::Interface(index as u32));
+        member_expr.r#type = Some(func_type.into());
+    }
+
+    fn check_variant_member(
+        &mut self,
+        member_expr: &mut MemberExpr,
+        type_id: u64,
+        is_callee: bool,
+    ) {
+        let r#type = self.resolve_type(type_id);
+        let enum_type = r#type.cast::<EnumType>();
+        let index = enum_type
+            .variant_index(&member_expr.member.name)
+            .unwrap_or_else(|| {
+                panic!(
+                    "No variant `{}` in {:?}",
+                    member_expr.member.name, member_expr.postfix_expr
+                )
+            });
+
+        let mut self_type = RefType::new(member_expr.postfix_expr.clone());
+        self_type.type_id = Some(type_id);
+        member_expr.member_kind = Some(MemberKind::Variant(index as u32));
+        member_expr.r#type = match &enum_type.variants[index].payload {
+            Some(payload) if is_callee => Some(
+                FuncType::new(
+                    self_type.into(),
+                    vec![Param::new("payload".to_owned(), payload.clone())],
+                    false,
+                )
+                .into(),
+            ),
+            Some(_) => panic!(
+                "Variant `{}` carries a payload and must be called",
+                member_expr.member.name
+            ),
+            None if is_callee => panic!(
+                "Variant `{}` has no payload and cannot be called",
+                member_expr.member.name
+            ),
+            None => Some(self_type.into()),
+        };
+    }
+
+    fn check_static_owner(&mut self, expr: &mut Expr, r#type: &Option<Type>) -> Option<u64> {
+        let mut type_id = self.lookup_type_path(expr)?;
+        if self.generic_types.contains_key(&type_id) {
+            // The type arguments of a generic owner come from the expected type
+            type_id = r#type
+                .as_ref()
+                .filter(|r#type| r#type.kind() == TypeKind::Ref)
+                .map(|r#type| self.canonical_type_id(r#type.cast::<RefType>().type_id.unwrap()))
+                .filter(|instance_id| {
+                    self.instance_args
+                        .get(instance_id)
+                        .is_some_and(|(generic_id, _)| *generic_id == type_id)
+                })
+                .unwrap_or_else(|| {
+                    panic!(
+                        "Cannot infer the type arguments of generic type `{:?}`",
+                        expr
+                    )
+                });
+        }
+        if let ExprKind::Ident = expr.kind() {
+            expr.cast_mut::<IdentExpr>().ident.symbol_id = Some(type_id);
+        }
+        Some(type_id)
+    }
+
+    // Resolves an owner such as `Shape` or `Outer.Inner`, where unlike in type positions a
+    // path that names no type is an ordinary expression
+    fn lookup_type_path(&self, expr: &Expr) -> Option<u64> {
+        match expr.kind() {
+            ExprKind::Ident => {
+                let name = &expr.cast::<IdentExpr>().ident.name;
+                if self.symbol_table.retrieve_value(name).is_some() {
+                    return None;
+                }
+                Some(self.symbol_table.retrieve_type(name)?.type_id)
+            }
+            ExprKind::Member => {
+                let member_expr = expr.cast::<MemberExpr>();
+                let name = &member_expr.member.name;
+                if let Some(owner_id) = self.lookup_type_path(&member_expr.postfix_expr) {
+                    return self.nested_types.get(&(owner_id, name.clone())).copied();
+                }
+                let ExprKind::Ident = member_expr.postfix_expr.kind() else {
+                    return None;
+                };
+                let owner = &member_expr.postfix_expr.cast::<IdentExpr>().ident.name;
+                let module = self.module_namespaces.get(owner)?;
+                if self.symbol_table.retrieve_value(owner).is_some() {
+                    return None;
+                }
+                Some(
+                    self.symbol_table
+                        .retrieve_module_type(name, module)?
+                        .type_id,
+                )
+            }
+            _ => None,
+        }
+    }
+
+    fn check_composite_expr(&mut self, composite_expr: &mut CompositeExpr, r#type: &Option<Type>) {
+        if r#type.is_some() {
+            composite_expr.r#type = r#type.clone();
+        }
+
+        let Some(Some(type_id)) = composite_expr
+            .r#type
+            .as_ref()
+            .filter(|r#type| r#type.kind() == TypeKind::Ref)
+            .map(|r#type| r#type.cast::<RefType>().type_id)
+        else {
+            for (_, expr) in &mut composite_expr.fields {
+                self.check_expr(expr, &None)
+            }
+            return;
+        };
+
+        let composite_type = self.resolve_composite_type(type_id);
+        if composite_type.is_restricted() && self.is_foreign_type(type_id) {
+            panic!(
+                "Cannot construct restricted type {:?} outside of the file defining it",
+                composite_expr.r#type.as_ref().unwrap()
+            );
+        }
+        let mut field_indices = Vec::new();
+        for (name, expr) in &mut composite_expr.fields {
+            let index = composite_type
+                .field_index(name)
+                .unwrap_or_else(|| panic!("No field `{}` in {:?}", name, composite_expr.r#type));
+            if field_indices.contains(&(index as u32)) {
+                panic!(
+                    "Field `{}` specified twice in {:?}",
+                    name,
+                    composite_expr.r#type.as_ref().unwrap()
+                );
+            }
+            field_indices.push(index as u32);
+            let field_type = composite_type.fields[index].r#type.clone();
+            self.check_expr(expr, &Some(field_type.clone()));
+            if !self.try_coerce(expr, &field_type) {
+                panic!(
+                    "Field `{}` of {:?} has type {:?}, found {:?} of type {:?}",
+                    name,
+                    composite_expr.r#type.as_ref().unwrap(),
+                    field_type,
+                    expr,
+                    expr.r#type().as_ref().unwrap()
+                );
+            }
+        }
+        if let Some(field) = composite_type.fields.iter().find(|field| {
+            composite_expr
+                .fields
+                .iter()
+                .all(|(name, _)| *name != field.name)
+        }) {
+            panic!(
+                "Missing field `{}` in {:?}",
+                field.name,
+                composite_expr.r#type.as_ref().unwrap()
+            );
+        }
+        composite_expr.field_indices = field_indices;
+    }
+
+    fn check_tuple_expr(&mut self, tuple_expr: &mut TupleExpr, r#type: &Option<Type>) {
+        let expected = r#type
+            .as_ref()
+            .map(|r#type| self.resolve(r#type))
+            .filter(|r#type| r#type.kind() == TypeKind::Tuple);
+        let expected_types = expected
+            .as_ref()
+            .map(|r#type| r#type.cast::<TupleType>().elem_types.clone());
+        if let Some(expected_types) = &expected_types {
+            if expected_types.len() != tuple_expr.elems.len() {
+                panic!(
+                    "Expected a tuple of {} elements, found {:?} with {} elements",
+                    expected_types.len(),
+                    tuple_expr,
+                    tuple_expr.elems.len()
+                );
+            }
+        }
+
+        let mut elem_types = Vec::new();
+        for (i, elem) in tuple_expr.elems.iter_mut().enumerate() {
+            let expected_type = expected_types.as_ref().map(|types| types[i].clone());
+            self.check_expr(elem, &expected_type);
+            match expected_type {
+                Some(expected_type) => {
+                    self.coerce(elem, &expected_type);
+                    elem_types.push(expected_type);
+                }
+                None => elem_types.push(elem.r#type().clone().unwrap()),
+            }
+        }
+        tuple_expr.r#type = Some(TupleType::new(elem_types).into());
+    }
+
+    // Literals take the expected integer type, and must fit in it rather than wrap
+    fn check_int_literal_expr(&self, int_literal: &mut IntLiteralExpr, r#type: &Option<Type>) {
+        if let Some(r#type) = r#type {
+            if self.resolve(r#type).kind() == TypeKind::Int {
+                int_literal.r#type = Some(r#type.clone());
+            }
+        }
+        let literal_type = int_literal.r#type.as_ref().unwrap();
+        let int_type = self.resolve(literal_type);
+        let fits = int_literal
+            .value
+            .parse::<i128>()
+            .is_ok_and(|value| int_range(int_type.cast::<IntType>()).contains(&value));
+        if !fits {
+            panic!(
+                "Integer literal {} does not fit in {:?}",
+                int_literal.value, literal_type
+            );
+        }
+    }
+
+    fn check_null_literal_expr(&self, null_literal: &mut NullLiteralExpr, r#type: &Option<Type>) {
+        match r#type {
+            Some(r#type) if self.resolve(r#type).kind() == TypeKind::Optional => {
+                null_literal.r#type = Some(r#type.clone());
+            }
+            Some(r#type) => panic!(
+                "`null` is not a valid value of non-optional type {:?}",
+                r#type
+            ),
+            None => panic!("Cannot infer the optional type of `null`"),
+        }
+    }
+
+    fn check_array_expr(&mut self, array_expr: &mut ArrayExpr, r#type: &Option<Type>) {
+        let len = array_expr.elems.len() as u32;
+        let mut elem_type = self.expected_elem_type(r#type, len);
+        for elem in &mut array_expr.elems {
+            self.check_expr(elem, &elem_type);
+            match &elem_type {
+                Some(elem_type) => self.coerce(elem, elem_type),
+                None => elem_type = elem.r#type().clone(),
+            }
+        }
+
+        let elem_type = elem_type
+            .unwrap_or_else(|| panic!("Cannot infer the element type of {:?}", array_expr));
+        array_expr.r#type = Some(ArrayType::new(elem_type, Some(len)).into());
+    }
+
+    fn check_array_repeat_expr(
+        &mut self,
+        array_repeat_expr: &mut ArrayRepeatExpr,
+        r#type: &Option<Type>,
+    ) {
+        let elem_type = self.expected_elem_type(r#type, array_repeat_expr.len);
+        self.check_expr(&mut array_repeat_expr.value, &elem_type);
+        let elem_type = match elem_type {
+            Some(elem_type) => {
+                self.coerce(&mut array_repeat_expr.value, &elem_type);
+                elem_type
+            }
+            None => array_repeat_expr.value.r#type().clone().unwrap(),
+        };
+        array_repeat_expr.r#type =
+            Some(ArrayType::new(elem_type, Some(array_repeat_expr.len)).into());
+    }
+
+    fn expected_elem_type(&self, r#type: &Option<Type>, len: u32) -> Option<Type> {
+        let r#type = self.resolve(r#type.as_ref()?);
+        if r#type.kind() != TypeKind::Array {
+            return None;
+        }
+        let array_type = r#type.cast::<ArrayType>();
+        if array_type
+            .len
+            .is_some_and(|expected_len| expected_len != len)
+        {
+            panic!(
+                "Expected an array of length {}, found an array of length {}",
+                array_type.len.unwrap(),
+                len
+            );
+        }
+        Some(array_type.elem_type.clone())
+    }
+
+    fn check_index_expr(&mut self, index_expr: &mut IndexExpr) {
+        self.check_expr(&mut index_expr.postfix_expr, &None);
+        self.check_expr(&mut index_expr.index, &None);
+        self.check_array_index(&index_expr.index);
+
+        let array_type = self.indexed_array_type(&index_expr.postfix_expr);
+        index_expr.r#type = Some(array_type.elem_type);
+    }
+
+    fn check_slice_expr(&mut self, slice_expr: &mut SliceExpr) {
+        self.check_expr(&mut slice_expr.postfix_expr, &None);
+        for bound in [&mut slice_expr.lo, &mut slice_expr.hi]
+            .into_iter()
+            .flatten()
+        {
+            self.check_expr(bound, &None);
+            self.check_array_index(bound);
+        }
+
+        let array_type = self.indexed_array_type(&slice_expr.postfix_expr);
+        slice_expr.r#type = Some(ArrayType::new(array_type.elem_type, None).into());
+    }
+
+    fn check_array_index(&self, index: &Expr) {
+        let index_type = self.resolve(index.r#type().as_ref().unwrap());
+        if index_type.kind() != TypeKind::Int {
+            panic!("Array index {:?} must be an integer", index);
+        }
+    }
+
+    fn indexed_array_type(&self, expr: &Expr) -> ArrayType {
+        let mut owner_type = expr.r#type().clone().unwrap();
+        if owner_type.kind() == TypeKind::Ptr {
+            owner_type = owner_type.cast::<PtrType>().pointee.clone();
+        }
+        let owner_type = self.resolve(&owner_type);
+        if owner_type.kind() != TypeKind::Array {
+            panic!("Cannot index into {:?} of type {:?}", expr, owner_type);
+        }
+        owner_type.cast::<ArrayType>().clone()
+    }
+
+    fn check_binary_expr(&mut self, binary_expr: &mut BinaryExpr, r#type: &Option<Type>) {
+        let op = binary_expr.op;
+        if op == BinaryOp::Coalesce {
+            self.check_coalesce_expr(binary_expr);
+            return;
+        }
+
+        // Integer and null literals take the type of the other operand
+        let expected = r#type.clone().filter(|_| !op.is_comparison());
+        if matches!(
+            binary_expr.lhs.kind(),
+            ExprKind::IntLiteral | ExprKind::NullLiteral
+        ) && !matches!(binary_expr.rhs.kind(), ExprKind::IntLiteral)
+        {
+            self.check_expr(&mut binary_expr.rhs, &expected);
+            let rhs_type = binary_expr.rhs.r#type().clone();
+            self.check_expr(&mut binary_expr.lhs, &rhs_type);
+        } else {
+            self.check_expr(&mut binary_expr.lhs, &expected);
+            let lhs_type = binary_expr.lhs.r#type().clone();
+            self.check_expr(&mut binary_expr.rhs, &lhs_type);
+            self.try_coerce(&mut binary_expr.rhs, lhs_type.as_ref().unwrap());
+        }
+
+        let lhs_type = binary_expr.lhs.r#type().clone().unwrap();
+        let rhs_type = binary_expr.rhs.r#type().clone().unwrap();
+        if self.mangle_type(&lhs_type) != self.mangle_type(&rhs_type) {
+            panic!(
+                "Mismatched operands of `{}` in {:?}: {:?} and {:?}",
+                op.symbol(),
+                binary_expr,
+                lhs_type,
+                rhs_type
+            );
+        }
+
+        let resolved_type = self.resolve(&lhs_type);
+        let is_defined = match resolved_type.kind() {
+            TypeKind::Int => true,
+            TypeKind::Bool => matches!(op, BinaryOp::Eq | BinaryOp::Ne),
+            TypeKind::Composite => {
+                self.check_operator_method(binary_expr, &lhs_type);
+                true
+            }
+            TypeKind::Optional => {
+                if !is_null_test(binary_expr) {
+                    panic!(
+                        "Optional values can only be compared with `null`, found {:?}",
+                        binary_expr
+                    );
+                }
+                matches!(op, BinaryOp::Eq | BinaryOp::Ne)
+            }
+            _ => false,
+        };
+        if !is_defined {
+            panic!(
+                "Operator `{}` is not defined for {:?}",
+                op.symbol(),
+                lhs_type
+            );
+        }
+
+        binary_expr.r#type = Some(match op.is_comparison() {
+            true => BoolType.into(),
+            false => lhs_type,
+        });
+    }
+
+    fn check_coalesce_expr(&mut self, binary_expr: &mut BinaryExpr) {
+        self.check_expr(&mut binary_expr.lhs, &None);
+        let lhs_type = self.resolve(binary_expr.lhs.r#type().as_ref().unwrap());
+        if lhs_type.kind() != TypeKind::Optional {
+            panic!(
+                "Left operand of `??` must be optional, found {:?} of type {:?}",
+                binary_expr.lhs, lhs_type
+            );
+        }
+
+        let inner = lhs_type.cast::<OptionalType>().inner.clone();
+        // The right operand is only evaluated if the left one is null
+        let init_state = self.init_state.clone();
+        self.check_expr(&mut binary_expr.rhs, &Some(inner.clone()));
+        self.init_state.merge(init_state);
+        self.coerce(&mut binary_expr.rhs, &inner);
+        binary_expr.r#type = Some(inner);
+    }
+
+    // `a != b`, `a > b`, `a <= b` and `a >= b` are derived from the `==` and `<` methods
+    fn check_operator_method(&self, binary_expr: &mut BinaryExpr, r#type: &Type) {
+        let (name, is_swapped, is_negated) = match binary_expr.op {
+            BinaryOp::Add => ("op_add", false, false),
+            BinaryOp::Sub => ("op_sub", false, false),
+            BinaryOp::Mul => ("op_mul", false, false),
+            BinaryOp::Div => ("op_div", false, false),
+            BinaryOp::Eq => ("op_eq", false, false),
+            BinaryOp::Ne => ("op_eq", false, true),
+            BinaryOp::Lt => ("op_lt", false, false),
+            BinaryOp::Gt => ("op_lt", true, false),
+            BinaryOp::Le => ("op_lt", true, true),
+            BinaryOp::Ge => ("op_lt", false, true),
+            BinaryOp::Coalesce => unreachable!(),
+        };
+        let type_id = r#type.cast::<RefType>().type_id.unwrap();
+        let decl = self.get_static(type_id, name).unwrap_or_else(|| {
+            panic!(
+                "Operator `{}` is not defined for {:?}, it needs a static `{}` method",
+                binary_expr.op.symbol(),
+                r#type,
+                name
+            )
+        });
+        binary_expr.method_id = decl.value_id;
+        binary_expr.is_swapped = is_swapped;
+        binary_expr.is_negated = is_negated;
+    }
+
+    fn check_if_expr(&mut self, if_expr: &mut IfExpr, r#type: &Option<Type>) {
+        self.check_expr(&mut if_expr.cond, &Some(BoolType.into()));
+        if self.resolve(if_expr.cond.r#type().as_ref().unwrap()).kind() != TypeKind::Bool {
+            panic!("Condition {:?} must be a bool", if_expr.cond);
+        }
+
+        // The branch in which a tested value is not null sees it with its inner type
+        let null_test = self.null_test(&if_expr.cond);
+        let narrowed_len = self.narrowed_values.len();
+        if let Some((value_id, false)) = null_test {
+            self.narrowed_values.push(value_id);
+        }
+        let init_state = self.init_state.clone();
+        let then_type = self.check_block(&mut if_expr.then_body, r#type);
+        let then_state = mem::replace(&mut self.init_state, init_state);
+        let then_diverges = self.diverges(&if_expr.then_body);
+        self.narrowed_values.truncate(narrowed_len);
+
+        let Some(else_body) = &mut if_expr.else_body else {
+            // `if x == null { return }` narrows `x` in the rest of the enclosing block
+            if let (Some((value_id, true)), true) = (null_test, then_diverges) {
+                self.narrowed_values.push(value_id);
+            }
+            if !then_diverges {
+                self.init_state.merge(then_state);
+            }
+            if_expr.r#type = Some(VoidType.into());
+            return;
+        };
+
+        if let Some((value_id, true)) = null_test {
+            self.narrowed_values.push(value_id);
+        }
+        let expected = match then_diverges {
+            true => r#type.clone(),
+            false => r#type.clone().or(then_type.clone()),
+        };
+        let else_type = self.check_block(else_body, &expected);
+        self.narrowed_values.truncate(narrowed_len);
+
+        // Values assigned in both branches are initialised after the if, and a branch that
+        // never completes does not count
+        let else_diverges = self.diverges(else_body);
+        match (then_diverges, else_diverges) {
+            (false, false) => self.init_state.merge(then_state),
+            (false, true) => self.init_state = then_state,
+            _ => (),
+        }
+        if then_diverges && else_diverges {
+            if_expr.r#type = Some(NeverType.into());
+            return;
+        }
+
+        // Both branches must produce a value of the same type unless one of them diverges
+        if_expr.r#type = match (then_type, else_type) {
+            (Some(then_type), Some(else_type))
+                if r#type.is_some()
+                    || self.mangle_type(&then_type) == self.mangle_type(&else_type) =>
+            {
+                expected
+            }
+            (Some(_), None) if else_diverges => expected,
+            (None, Some(else_type)) if then_diverges => expected.or(Some(else_type)),
+            _ => None,
+        }
+        .filter(|r#type| r#type.kind() != TypeKind::Void)
+        .or_else(|| Some(VoidType.into()));
+    }
+
+    fn check_try_expr(&mut self, try_expr: &mut TryExpr) {
+        self.check_expr(&mut try_expr.expr, &None);
+        let expr_type = try_expr.expr.r#type().clone().unwrap();
+        let [ok_type, err_type] = self.result_args(&expr_type).unwrap_or_else(|| {
+            panic!(
+                "`?` can only be applied to a Result, found {:?} of type {:?}",
+                try_expr.expr, expr_type
+            )
+        });
+
+        let return_type = self
+            .return_types
+            .last()
+            .cloned()
+            .expect("`?` can only be used inside a function");
+        let [_, return_err_type] = self.result_args(&return_type).unwrap_or_else(|| {
+            panic!(
+                "`?` can only be used in a function returning a Result, not {:?}",
+                return_type
+            )
+        });
+        if self.mangle_type(&err_type) != self.mangle_type(&return_err_type) {
+            panic!(
+                "Error type {:?} of {:?} does not match the error type {:?} returned by the enclosing function",
+                err_type, try_expr.expr, return_err_type
+            );
+        }
+
+        try_expr.err_type = Some(err_type);
+        try_expr.return_type = Some(return_type);
+        try_expr.r#type = Some(ok_type);
+    }
+
+    // The `Ok` and `Err` types of an instance of `Result`
+    fn result_args(&self, r#type: &Type) -> Option<[Type; 2]> {
+        if r#type.kind() != TypeKind::Ref {
+            return None;
+        }
+        let type_id = self.canonical_type_id(r#type.cast::<RefType>().type_id.unwrap());
+        let (generic_id, type_args) = self.instance_args.get(&type_id)?;
+        if Some(*generic_id) != self.result_type_id {
+            return None;
+        }
+        type_args.clone().try_into().ok()
+    }
+
+    // Checks a list of statements in its own scope and returns the type of a trailing
+    // expression, which is coerced to the expected type
+    fn check_block(&mut self, stmts: &mut Vec<Stmt>, r#type: &Option<Type>) -> Option<Type> {
+        hoist_nested_types(stmts);
+        self.symbol_table.open_scope();
+        let narrowed_len = self.narrowed_values.len();
+        let len = stmts.len();
+        let mut tail_type = None;
+        for (i, stmt) in stmts.iter_mut().enumerate() {
+            if i + 1 < len || !matches!(stmt.kind(), StmtKind::Expr) {
+                self.check_stmt(stmt);
+                continue;
+            }
+            let expr = &mut stmt.cast_mut::<ExprStmt>().expr;
+            let is_unused = r#type
+                .as_ref()
+                .is_some_and(|r#type| r#type.kind() == TypeKind::Void);
+            match is_unused {
+                true => self.check_unused_expr(expr),
+                false => self.check_expr(expr, r#type),
+            }
+            if self.expr_diverges(expr) {
+                continue;
+            }
+            match r#type {
+                Some(_) if is_unused => (),
+                Some(r#type) => self.coerce(expr, r#type),
+                None => (),
+            }
+            tail_type = match is_unused {
+                true => r#type.clone(),
+                false => expr.r#type().clone(),
+            };
+        }
+        self.check_reachability(stmts);
+        self.narrowed_values.truncate(narrowed_len);
+        self.symbol_table.close_scope();
+        tail_type
+    }
+
+    fn diverges(&self, stmts: &[Stmt]) -> bool {
+        stmts.iter().any(|stmt| self.stmt_diverges(stmt))
+    }
+
+    fn stmt_diverges(&self, stmt: &Stmt) -> bool {
+        match stmt.kind() {
+            StmtKind::Return => true,
+            StmtKind::Expr => self.expr_diverges(&stmt.cast::<ExprStmt>().expr),
+            _ => false,
+        }
+    }
+
+    // Calls to functions returning never, and ifs and matches none of whose branches
+    // complete, have the type never
+    fn expr_diverges(&self, expr: &Expr) -> bool {
+        expr.r#type()
+            .as_ref()
+            .is_some_and(|r#type| r#type.kind() == TypeKind::Never)
+    }
+
+    // Whether control can reach the end of a list of statements, warning about code after
+    // a statement that never completes
+    fn check_reachability(&self, stmts: &[Stmt]) -> bool {
+        let Some(i) = stmts.iter().position(|stmt| self.stmt_diverges(stmt)) else {
+            return true;
+        };
+        if let Some(stmt) = stmts[i + 1..].iter().find(|stmt| is_executable(stmt)) {
+            eprintln!("warning: Unreachable statement: {:?}", stmt);
+        }
+        false
+    }
+
+    // Finds the immutable optional value tested by `x == null` or `x != null`
+    fn null_test(&self, expr: &Expr) -> Option<(u64, bool)> {
+        let ExprKind::Binary = expr.kind() else {
+            return None;
+        };
+        let binary_expr = expr.cast::<BinaryExpr>();
+        if !matches!(binary_expr.op, BinaryOp::Eq | BinaryOp::Ne) || !is_null_test(binary_expr) {
+            return None;
+        }
+        let operand = match binary_expr.lhs.kind() {
+            ExprKind::NullLiteral => &binary_expr.rhs,
+            _ => &binary_expr.lhs,
+        };
+        let ExprKind::Ident = operand.kind() else {
+            return None;
+        };
+        let value_id = operand.cast::<IdentExpr>().ident.symbol_id?;
+        if self.mutable_values.contains(&value_id) {
+            return None;
+        }
+        Some((value_id, binary_expr.op == BinaryOp::Eq))
+    }
+
+    fn optional_inner(&self, r#type: &Option<Type>) -> Option<Type> {
+        let r#type = self.resolve(r#type.as_ref()?);
+        if r#type.kind() != TypeKind::Optional {
+            return None;
+        }
+        Some(r#type.cast::<OptionalType>().inner.clone())
+    }
+
+    fn narrow(&self, expr: &mut Expr) {
+        let value_id = expr.cast::<IdentExpr>().ident.symbol_id;
+        if !value_id.is_some_and(|value_id| self.narrowed_values.contains(&value_id)) {
+            return;
+        }
+        let optional_type = self.resolve(expr.r#type().as_ref().unwrap());
+        let inner = optional_type.cast::<OptionalType>().inner.clone();
+        *expr = UnwrapExpr::new(expr.clone(), inner).into();
+    }
+
+    fn check_match_expr(&mut self, match_expr: &mut MatchExpr, r#type: &Option<Type>) {
+        self.check_expr(&mut match_expr.expr, &None);
+        let scrutinee_type = match_expr.expr.r#type().clone().unwrap();
+
+        let is_unused = r#type
+            .as_ref()
+            .is_some_and(|r#type| r#type.kind() == TypeKind::Void);
+        let mut expected = r#type.clone();
+        let mut diverges = true;
+        let init_state = self.init_state.clone();
+        let mut joined_state: Option<InitState> = None;
+        for arm in &mut match_expr.arms {
+            self.symbol_table.open_scope();
+            self.init_state = init_state.clone();
+
+            let mut bindings = Vec::new();
+            self.check_pattern(&mut arm.pattern, &scrutinee_type, &mut bindings);
+            let binding_ids: HashMap<String, u64> = bindings
+                .into_iter()
+                .map(|(name, r#type)| {
+                    let value_id = self.symbol_table.push_value(&name, Some(r#type));
+                    (name, value_id)
+                })
+                .collect();
+            bind_pattern_ids(&mut arm.pattern, &binding_ids);
+
+            if let Some(guard) = &mut arm.guard {
+                self.check_expr(guard, &Some(BoolType.into()));
+                if self.resolve(guard.r#type().as_ref().unwrap()).kind() != TypeKind::Bool {
+                    panic!("Match guard {:?} must be a bool", guard);
+                }
+            }
+
+            match is_unused {
+                true => self.check_unused_expr(&mut arm.body),
+                false => self.check_expr(&mut arm.body, &expected),
+            }
+            // Arms that never complete do not contribute a value
+            if !self.expr_diverges(&arm.body) {
+                diverges = false;
+                match &expected {
+                    _ if is_unused => (),
+                    Some(r#type) => self.coerce(&mut arm.body, r#type),
+                    None => expected = arm.body.r#type().clone(),
+                }
+                let arm_state = mem::take(&mut self.init_state);
+                match &mut joined_state {
+                    Some(joined_state) => joined_state.merge(arm_state),
+                    None => joined_state = Some(arm_state),
+                }
+            }
+
+            self.symbol_table.close_scope();
+        }
+        self.init_state = joined_state.unwrap_or(init_state);
+        match_expr.r#type = match diverges {
+            true => Some(NeverType.into()),
+            false => Some(expected.unwrap_or_else(|| VoidType.into())),
+        };
+
+        let mut rows: Vec<Vec<Pattern>> = Vec::new();
+        for arm in &match_expr.arms {
+            let row = vec![arm.pattern.clone()];
+            if self
+                .find_witness(&rows, &row, slice::from_ref(&scrutinee_type))
+                .is_none()
+            {
+                eprintln!("warning: Unreachable match arm: {:?}", arm.pattern);
+            }
+            if arm.guard.is_none() {
+                rows.push(row);
+            }
+        }
+        if let Some(witness) = self.find_witness(
+            &rows,
+            &[Pattern::Wildcard],
+            slice::from_ref(&scrutinee_type),
+        ) {
+            panic!(
+                "Non-exhaustive match on {:?}: pattern `{}` is not covered",
+                match_expr.expr, witness[0]
+            );
+        }
+    }
+
+    fn check_func_expr(&mut self, func_expr: &mut FuncExpr) {
+        self.check_func_signature(func_expr, None);
+        self.check_func_body(func_expr, None);
+    }
+
+    fn check_func_signature(&mut self, func_expr: &mut FuncExpr, self_type: Option<&Type>) {
+        if !func_expr.type_params.is_empty() {
+            panic!("Generic functions can only be declared by a top-level `let`");
+        }
+        for (i, param) in func_expr.params.iter().enumerate() {
+            if func_expr.params[..i]
+                .iter()
+                .any(|other| other.name == param.name)
+            {
+                panic!("Parameter `{}` is declared more than once", param.name);
+            }
+        }
+        for param in &mut func_expr.params {
+            self.check_param(param);
+        }
+        if func_expr.is_method {
+            let self_type = self_type.expect("`self` is only allowed in methods of named types");
+            func_expr.params.insert(
+                0,
+                Param::new("self".to_string(), PtrType::new(self_type.clone()).into()),
+            );
+        }
+
+        let return_type = match &mut func_expr.return_type {
+            Some(return_type) => {
+                self.check_return_type(return_type);
+                return_type.clone()
+            }
+            None => VoidType.into(),
+        };
+        let func_type = FuncType::new(return_type, func_expr.params.clone(), func_expr.is_var_args);
+        self.check_var_args(&func_type);
+        func_expr.r#type = Some(func_type.into());
+    }
+
+    fn check_func_body(&mut self, func_expr: &mut FuncExpr, value_id: Option<u64>) {
+        // Globals are zero-initialised, so a function may read them whenever it is called and
+        // only its own locals are tracked
+        let outer_init_state = mem::take(&mut self.init_state);
+        self.symbol_table.open_scope();
+        for param in &mut func_expr.params {
+            let value_id = self
+                .symbol_table
+                .push_value(&param.name, Some(param.r#type.clone()));
+            param.value_id = Some(value_id);
+        }
+
+        let func_type = func_expr.r#type.as_ref().unwrap().cast::<FuncType>();
+        self.return_types.push(func_type.return_type.clone());
+        let narrowed_len = self.narrowed_values.len();
+        hoist_nested_types(&mut func_expr.body);
+        for stmt in &mut func_expr.body {
+            self.check_stmt(stmt);
+        }
+        func_expr.is_end_reachable = self.check_reachability(&func_expr.body);
+        let return_type = &func_type.return_type;
+        if func_expr.is_end_reachable && return_type.kind() == TypeKind::Never {
+            panic!(
+                "Function of type {:?} can reach its end, but must never return",
+                func_type
+            );
+        }
+        if func_expr.is_end_reachable && self.resolve(return_type).kind() != TypeKind::Void {
+            panic!(
+                "Function of type {:?} can reach its end without returning a value of type {:?}",
+                func_type, return_type
+            );
+        }
+        let kept_params = self.check_escapes(func_expr);
+        if let Some(value_id) = value_id {
+            self.kept_params.insert(value_id, kept_params);
+        }
+        self.narrowed_values.truncate(narrowed_len);
+        self.return_types.pop();
+        self.symbol_table.close_scope();
+        self.init_state = outer_init_state;
+    }
+
+    // ==================================================
+
+    // Composite values live on the stack of the function creating them, while interface
+    // values and slices point at that storage instead of copying it. Storage whose address
+    // may outlive the function, because a value referring to it is returned, stored into a
+    // global or stored through a pointer, is allocated on the heap instead and never freed.
+    // A value stored into a local escapes when that local does, and pointers, including
+    // `self`, are assumed to point at storage outliving the function. A call may return
+    // references to its arguments, and keeps those of the parameters its callee keeps,
+    // which are returned here. Callees checked later, including recursive ones, and those
+    // called through a value or an interface may keep any argument, except that interface
+    // values are kept when wrapped if a method implementing the interface keeps `self`.
+    // Top-level code runs in `main`, whose storage lasts for the whole program
+    fn check_escapes(&self, func_expr: &mut FuncExpr) -> Vec<bool> {
+        let mut analysis = EscapeAnalysis::default();
+        for param in &func_expr.params {
+            analysis.locals.insert(param.value_id.unwrap());
+        }
+        self.walk_escapes(&mut func_expr.body, &mut analysis);
+        analysis.solve();
+        // Callers track what is returned themselves, so parameters are kept when they
+        // escape otherwise
+        let kept_params = func_expr
+            .params
+            .iter()
+            .map(|param| analysis.escaping_values.contains(&param.value_id.unwrap()))
+            .collect();
+        let returns = mem::take(&mut analysis.returns);
+        analysis
+            .flows
+            .extend(returns.into_iter().map(|flows| (None, flows)));
+        analysis.solve();
+
+        // Now that it is known which locals escape, the same walk marks the storage to promote
+        analysis.is_marking = true;
+        self.walk_escapes(&mut func_expr.body, &mut analysis);
+        for param in &mut func_expr.params {
+            param.is_escaping = analysis.heap_values.contains(&param.value_id.unwrap());
+        }
+        kept_params
+    }
+
+    fn walk_escapes(&self, stmts: &mut [Stmt], analysis: &mut EscapeAnalysis) {
+        for stmt in stmts {
+            match stmt.kind() {
+                StmtKind::Decl => {
+                    for decl in &mut stmt.cast_mut::<DeclStmt>().decls {
+                        self.escape_decl(decl, analysis);
+                    }
+                }
+                StmtKind::Assign => {
+                    let assign_stmt = stmt.cast_mut::<AssignStmt>();
+                    let target = self.escape_target(&assign_stmt.target, analysis);
+                    self.flow_into(target.as_slice(), &mut assign_stmt.value, analysis);
+                }
+                StmtKind::Expr => {
+                    let expr = &mut stmt.cast_mut::<ExprStmt>().expr;
+                    self.escape_flows(expr, &mut Flows::default(), false, analysis);
+                }
+                StmtKind::Return => {
+                    if let Some(expr) = &mut stmt.cast_mut::<ReturnStmt>().expr {
+                        let mut flows = Flows::default();
+                        self.escape_flows(expr, &mut flows, true, analysis);
+                        analysis.returns.push(flows);
+                    }
+                }
+                // Functions declared in types have frames of their own
+                StmtKind::Type | StmtKind::Extend | StmtKind::Extern => (),
+            }
+        }
+    }
+
+    fn escape_decl(&self, decl: &mut Decl, analysis: &mut EscapeAnalysis) {
+        if is_func_decl(decl) {
+            return;
+        }
+        let value_ids = match &decl.pattern {
+            Some(pattern) => pattern.value_ids(),
+            None => vec![decl.value_id.unwrap()],
+        };
+        analysis.locals.extend(&value_ids);
+        if let Some(value) = &mut decl.value {
+            self.flow_into(&value_ids, value, analysis);
+        }
+        if analysis.is_marking {
+            decl.is_escaping = value_ids
+                .iter()
+                .any(|value_id| analysis.heap_values.contains(value_id));
+        }
+    }
+
+    // The local whose storage an assignment writes to, or `None` when it writes to storage
+    // outliving the function
+    fn escape_target(&self, target: &Expr, analysis: &EscapeAnalysis) -> Option<u64> {
+        match target.kind() {
+            ExprKind::Ident => target
+                .cast::<IdentExpr>()
+                .ident
+                .symbol_id
+                .filter(|value_id| analysis.locals.contains(value_id)),
+            ExprKind::Member => {
+                let member_expr = target.cast::<MemberExpr>();
+                match member_expr.member_kind {
+                    Some(MemberKind::Field(_)) if !self.is_ptr(&member_expr.postfix_expr) => {
+                        self.escape_target(&member_expr.postfix_expr, analysis)
+                    }
+                    _ => None,
+                }
+            }
+            ExprKind::Index => {
+                let index_expr = target.cast::<IndexExpr>();
+                match self.is_fixed_array(&index_expr.postfix_expr) {
+                    true => self.escape_target(&index_expr.postfix_expr, analysis),
+                    false => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    // Records that the references in a value flow into the given locals, or out of the
+    // function when there are none
+    fn flow_into(&self, value_ids: &[u64], expr: &mut Expr, analysis: &mut EscapeAnalysis) {
+        let is_escaping = value_ids.is_empty()
+            || value_ids
+                .iter()
+                .any(|value_id| analysis.escaping_values.contains(value_id));
+        let mut flows = Flows::default();
+        self.escape_flows(expr, &mut flows, is_escaping, analysis);
+        match value_ids {
+            [] => analysis.flows.push((None, flows)),
+            _ => {
+                for value_id in value_ids {
+                    analysis.flows.push((Some(*value_id), flows.clone()));
+                }
+            }
+        }
+    }
+
+    // Collects the locals a value may refer to, marking temporaries it refers to for
+    // promotion when the value escapes
+    fn escape_flows(
+        &self,
+        expr: &mut Expr,
+        flows: &mut Flows,
+        is_escaping: bool,
+        analysis: &mut EscapeAnalysis,
+    ) {
+        // Values that cannot refer to any storage only need their blocks walked
+        let mut unused = Flows::default();
+        let (flows, is_escaping) = match expr.r#type() {
+            Some(r#type) if self.carries_refs(r#type) => (flows, is_escaping),
+            _ => (&mut unused, false),
+        };
+        match expr.kind() {
+            ExprKind::Ident => {
+                let value_id = expr.cast::<IdentExpr>().ident.symbol_id.unwrap();
+                if analysis.locals.contains(&value_id) {
+                    flows.values.push(value_id);
+                }
+            }
+            ExprKind::Dyn => {
+                // Calls through an interface are passed the data as `self`, so the data is
+                // kept when a method implementing the interface keeps its receiver
+                let dyn_expr = expr.cast_mut::<DynExpr>();
+                let is_kept = dyn_expr
+                    .method_ids
+                    .iter()
+                    .any(|method_id| self.kept_params.get(method_id).is_none_or(|kept| kept[0]));
+                let is_escaping = is_kept || is_escaping;
+                let mut data_flows = Flows::default();
+                if self.is_ptr(&dyn_expr.expr) {
+                    self.escape_flows(&mut dyn_expr.expr, &mut data_flows, is_escaping, analysis);
+                } else {
+                    let is_place = self.place_flows(
+                        &mut dyn_expr.expr,
+                        &mut data_flows,
+                        is_escaping,
+                        analysis,
+                    );
+                    if analysis.is_marking {
+                        dyn_expr.is_escaping = is_escaping && !is_place;
+                    }
+                }
+                analysis.pass(flows, data_flows, is_kept);
+            }
+            ExprKind::Slice => {
+                let slice_expr = expr.cast_mut::<SliceExpr>();
+                for bound in slice_expr.lo.iter_mut().chain(&mut slice_expr.hi) {
+                    self.escape_flows(bound, &mut Flows::default(), false, analysis);
+                }
+                // String literals are global, and other slices refer to storage of their own
+                let postfix_expr = &mut slice_expr.postfix_expr;
+                if !self.is_fixed_array(postfix_expr) {
+                    self.escape_flows(postfix_expr, flows, is_escaping, analysis);
+                    return;
+                }
+                let is_place = self.place_flows(postfix_expr, flows, is_escaping, analysis);
+                if analysis.is_marking {
+                    slice_expr.is_escaping = is_escaping && !is_place;
+                }
+            }
+            ExprKind::Member => {
+                let member_expr = expr.cast_mut::<MemberExpr>();
+                self.escape_flows(&mut member_expr.postfix_expr, flows, is_escaping, analysis);
+            }
+            ExprKind::Index => {
+                let index_expr = expr.cast_mut::<IndexExpr>();
+                self.escape_flows(
+                    &mut index_expr.index,
+                    &mut Flows::default(),
+                    false,
+                    analysis,
+                );
+                self.escape_flows(&mut index_expr.postfix_expr, flows, is_escaping, analysis);
+            }
+            ExprKind::Call => {
+                let call_expr = expr.cast_mut::<CallExpr>();
+                let kept_params = self.callee_kept_params(call_expr);
+                // C functions are passed their variadic arguments beyond their parameters
+                let is_kept = |i: usize| {
+                    kept_params
+                        .as_ref()
+                        .is_none_or(|kept| kept.get(i).is_some_and(|is_kept| *is_kept))
+                };
+                let is_method = is_method_call(&call_expr.postfix_expr);
+                for (i, arg) in call_expr.args.iter_mut().enumerate() {
+                    let is_kept = is_kept(i + is_method as usize);
+                    let mut arg_flows = Flows::default();
+                    let is_escaping = is_kept || is_escaping;
+                    self.escape_flows(&mut arg.expr, &mut arg_flows, is_escaping, analysis);
+                    analysis.pass(flows, arg_flows, is_kept);
+                }
+                // A method may return `self`, which points at its receiver
+                let postfix_expr = &mut call_expr.postfix_expr;
+                if !is_method {
+                    self.escape_flows(postfix_expr, &mut Flows::default(), false, analysis);
+                    return;
+                }
+                let member_expr = postfix_expr.cast_mut::<MemberExpr>();
+                let receiver = &mut member_expr.postfix_expr;
+                let mut receiver_flows = Flows::default();
+                // The receiver of a call through an interface was accounted for when its
+                // data was wrapped
+                let is_receiver_kept = match member_expr.member_kind {
+                    Some(MemberKind::Interface(_)) => false,
+                    _ => is_kept(0),
+                };
+                let is_escaping = is_receiver_kept || is_escaping;
+                match member_expr.member_kind {
+                    Some(MemberKind::Method(_)) if !self.is_ptr(receiver) => {
+                        let is_place =
+                            self.place_flows(receiver, &mut receiver_flows, is_escaping, analysis);
+                        if analysis.is_marking {
+                            call_expr.is_escaping = is_escaping && !is_place;
+                        }
+                    }
+                    _ => self.escape_flows(receiver, &mut receiver_flows, is_escaping, analysis),
+                }
+                analysis.pass(flows, receiver_flows, is_receiver_kept);
+            }
+            ExprKind::Composite => {
+                for (_, expr) in &mut expr.cast_mut::<CompositeExpr>().fields {
+                    self.escape_flows(expr, flows, is_escaping, analysis);
+                }
+            }
+            ExprKind::Array => {
+                for elem in &mut expr.cast_mut::<ArrayExpr>().elems {
+                    self.escape_flows(elem, flows, is_escaping, analysis);
+                }
+            }
+            ExprKind::Tuple => {
+                for elem in &mut expr.cast_mut::<TupleExpr>().elems {
+                    self.escape_flows(elem, flows, is_escaping, analysis);
+                }
+            }
+            ExprKind::ArrayRepeat => {
+                let value = &mut expr.cast_mut::<ArrayRepeatExpr>().value;
+                self.escape_flows(value, flows, is_escaping, analysis);
+            }
+            ExprKind::Wrap => {
+                let inner = &mut expr.cast_mut::<WrapExpr>().expr;
+                self.escape_flows(inner, flows, is_escaping, analysis);
+            }
+            ExprKind::Unwrap => {
+                let inner = &mut expr.cast_mut::<UnwrapExpr>().expr;
+                self.escape_flows(inner, flows, is_escaping, analysis);
+            }
+            ExprKind::Upcast => {
+                let inner = &mut expr.cast_mut::<UpcastExpr>().expr;
+                self.escape_flows(inner, flows, is_escaping, analysis);
+            }
+            // The error of `?` is returned from the function
+            ExprKind::Try => {
+                let inner = &mut expr.cast_mut::<TryExpr>().expr;
+                let mut inner_flows = Flows::default();
+                self.escape_flows(inner, &mut inner_flows, true, analysis);
+                flows.values.extend(&inner_flows.values);
+                flows.addresses.extend(&inner_flows.addresses);
+                analysis.returns.push(inner_flows);
+            }
+            // Operators of named types call methods, which are passed both operands
+            ExprKind::Binary => {
+                let binary_expr = expr.cast_mut::<BinaryExpr>();
+                let Some(method_id) = binary_expr.method_id else {
+                    self.escape_flows(&mut binary_expr.lhs, &mut Flows::default(), false, analysis);
+                    self.escape_flows(&mut binary_expr.rhs, &mut Flows::default(), false, analysis);
+                    return;
+                };
+                let kept_params = self.kept_params.get(&method_id);
+                let is_kept = |i: usize| kept_params.is_none_or(|kept| kept[i]);
+                let operands = match binary_expr.is_swapped {
+                    true => [&mut binary_expr.rhs, &mut binary_expr.lhs],
+                    false => [&mut binary_expr.lhs, &mut binary_expr.rhs],
+                };
+                for (i, operand) in operands.into_iter().enumerate() {
+                    let mut operand_flows = Flows::default();
+                    let is_escaping = is_kept(i) || is_escaping;
+                    self.escape_flows(operand, &mut operand_flows, is_escaping, analysis);
+                    analysis.pass(flows, operand_flows, is_kept(i));
+                }
+            }
+            ExprKind::If => {
+                let if_expr = expr.cast_mut::<IfExpr>();
+                self.escape_flows(&mut if_expr.cond, &mut Flows::default(), false, analysis);
+                self.escape_block(&mut if_expr.then_body, flows, is_escaping, analysis);
+                if let Some(else_body) = &mut if_expr.else_body {
+                    self.escape_block(else_body, flows, is_escaping, analysis);
+                }
+            }
+            ExprKind::Match => {
+                self.escape_match(expr.cast_mut::<MatchExpr>(), flows, is_escaping, analysis)
+            }
+            _ => (),
+        }
+    }
+
+    // Which parameters the callee of a call keeps, if known
+    fn callee_kept_params(&self, call_expr: &CallExpr) -> Option<Vec<bool>> {
+        let callee = &call_expr.postfix_expr;
+        let value_id = match callee.kind() {
+            ExprKind::Ident => callee.cast::<IdentExpr>().ident.symbol_id,
+            ExprKind::Member => {
+                let member_expr = callee.cast::<MemberExpr>();
+                match member_expr.member_kind {
+                    // Variants hold their payload like composites hold their fields
+                    Some(MemberKind::Variant(_)) => return Some(vec![false; call_expr.args.len()]),
+                    Some(MemberKind::Method(_) | MemberKind::Static) => {
+                        member_expr.member.symbol_id
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        self.kept_params.get(&value_id?).cloned()
+    }
+
+    // Collects the locals whose storage a value refers to, returning whether that storage
+    // is a place rather than a temporary
+    fn place_flows(
+        &self,
+        expr: &mut Expr,
+        flows: &mut Flows,
+        is_escaping: bool,
+        analysis: &mut EscapeAnalysis,
+    ) -> bool {
+        match expr.kind() {
+            ExprKind::Ident => {
+                let value_id = expr.cast::<IdentExpr>().ident.symbol_id.unwrap();
+                // A binding of a temporary refers to storage that cannot be promoted, so it
+                // is copied like one
+                if analysis.bindings.contains(&value_id) {
+                    let Some(&local_id) = analysis.aliases.get(&value_id) else {
+                        self.escape_flows(expr, flows, is_escaping, analysis);
+                        return false;
+                    };
+                    flows.values.push(local_id);
+                    flows.addresses.push(local_id);
+                } else if analysis.locals.contains(&value_id) {
+                    flows.values.push(value_id);
+                    flows.addresses.push(value_id);
+                }
+                true
+            }
+            ExprKind::Member => {
+                let member_expr = expr.cast_mut::<MemberExpr>();
+                match member_expr.member_kind {
+                    Some(MemberKind::Field(_)) if self.is_ptr(&member_expr.postfix_expr) => {
+                        let postfix_expr = &mut member_expr.postfix_expr;
+                        self.escape_flows(postfix_expr, flows, is_escaping, analysis);
+                        true
+                    }
+                    Some(MemberKind::Field(_)) => {
+                        let postfix_expr = &mut member_expr.postfix_expr;
+                        self.place_flows(postfix_expr, flows, is_escaping, analysis)
+                    }
+                    Some(MemberKind::Static) => true,
+                    _ => {
+                        self.escape_flows(expr, flows, is_escaping, analysis);
+                        false
+                    }
+                }
+            }
+            ExprKind::Index => {
+                let index_expr = expr.cast_mut::<IndexExpr>();
+                self.escape_flows(
+                    &mut index_expr.index,
+                    &mut Flows::default(),
+                    false,
+                    analysis,
+                );
+                let postfix_expr = &mut index_expr.postfix_expr;
+                match self.is_fixed_array(postfix_expr) {
+                    true => self.place_flows(postfix_expr, flows, is_escaping, analysis),
+                    false => {
+                        self.escape_flows(postfix_expr, flows, is_escaping, analysis);
+                        true
+                    }
+                }
+            }
+            ExprKind::Upcast => {
+                let inner = &mut expr.cast_mut::<UpcastExpr>().expr;
+                self.place_flows(inner, flows, is_escaping, analysis)
+            }
+            _ => {
+                self.escape_flows(expr, flows, is_escaping, analysis);
+                false
+            }
+        }
+    }
+
+    fn escape_block(
+        &self,
+        stmts: &mut [Stmt],
+        flows: &mut Flows,
+        is_escaping: bool,
+        analysis: &mut EscapeAnalysis,
+    ) {
+        let Some((tail, stmts)) = stmts.split_last_mut() else {
+            return;
+        };
+        self.walk_escapes(stmts, analysis);
+        match tail.kind() {
+            StmtKind::Expr => {
+                let expr = &mut tail.cast_mut::<ExprStmt>().expr;
+                self.escape_flows(expr, flows, is_escaping, analysis);
+            }
+            _ => self.walk_escapes(slice::from_mut(tail), analysis),
+        }
+    }
+
+    fn escape_match(
+        &self,
+        match_expr: &mut MatchExpr,
+        flows: &mut Flows,
+        is_escaping: bool,
+        analysis: &mut EscapeAnalysis,
+    ) {
+        // Bindings refer to the storage of the value being matched
+        let value_ids: Vec<u64> = match_expr
+            .arms
+            .iter()
+            .flat_map(|arm| arm.pattern.value_ids())
+            .collect();
+        analysis.locals.extend(&value_ids);
+        analysis.bindings.extend(&value_ids);
+        let local_id = self
+            .escape_target(&match_expr.expr, analysis)
+            .and_then(|local_id| match analysis.bindings.contains(&local_id) {
+                true => analysis.aliases.get(&local_id).copied(),
+                false => Some(local_id),
+            });
+        if let Some(local_id) = local_id {
+            for value_id in &value_ids {
+                analysis.aliases.insert(*value_id, local_id);
+            }
+        }
+        match value_ids.is_empty() {
+            true => self.escape_flows(&mut match_expr.expr, &mut Flows::default(), false, analysis),
+            false => self.flow_into(&value_ids, &mut match_expr.expr, analysis),
+        }
+
+        for arm in &mut match_expr.arms {
+            if let Some(guard) = &mut arm.guard {
+                self.escape_flows(guard, &mut Flows::default(), false, analysis);
+            }
+            self.escape_flows(&mut arm.body, flows, is_escaping, analysis);
+        }
+    }
+
+    // Whether a value of the type can refer to storage outside of itself
+    fn carries_refs(&self, r#type: &Type) -> bool {
+        let r#type = self.resolve(r#type);
+        match r#type.kind() {
+            TypeKind::Ptr | TypeKind::Interface => true,
+            TypeKind::Array => {
+                let array_type = r#type.cast::<ArrayType>();
+                array_type.len.is_none() || self.carries_refs(&array_type.elem_type)
+            }
+            TypeKind::Optional => self.carries_refs(&r#type.cast::<OptionalType>().inner),
+            TypeKind::Tuple => r#type
+                .cast::<TupleType>()
+                .elem_types
+                .iter()
+                .any(|elem_type| self.carries_refs(elem_type)),
+            TypeKind::Composite => r#type
+                .cast::<CompositeType>()
+                .fields
+                .iter()
+                .any(|field| self.carries_refs(&field.r#type)),
+            TypeKind::Enum => r#type
+                .cast::<EnumType>()
+                .variants
+                .iter()
+                .filter_map(|variant| variant.payload.as_ref())
+                .any(|payload| self.carries_refs(payload)),
+            _ => false,
+        }
+    }
+
+    fn is_ptr(&self, expr: &Expr) -> bool {
+        expr.r#type()
+            .as_ref()
+            .is_some_and(|r#type| r#type.kind() == TypeKind::Ptr)
+    }
+
+    fn is_fixed_array(&self, expr: &Expr) -> bool {
+        expr.r#type().as_ref().is_some_and(|r#type| {
+            let r#type = self.resolve(r#type);
+            r#type.kind() == TypeKind::Array && r#type.cast::<ArrayType>().len.is_some()
+        })
+    }
+
+    // ==================================================
+
+    fn check_type(&mut self, r#type: &mut Type) {
+        match r#type.kind() {
+            TypeKind::Ref => self.check_ref_type(r#type.cast_mut::<RefType>()),
+            TypeKind::Ptr => self.check_type(&mut r#type.cast_mut::<PtrType>().pointee),
+            TypeKind::Array => self.check_array_type(r#type.cast_mut::<ArrayType>()),
+            TypeKind::Func => self.check_func_type(r#type.cast_mut::<FuncType>()),
+            TypeKind::Composite => self.check_composite_type(r#type.cast_mut::<CompositeType>()),
+            TypeKind::Enum => self.check_enum_type(r#type.cast_mut::<EnumType>()),
+            TypeKind::Interface => {
+                for method in &mut r#type.cast_mut::<InterfaceType>().methods {
+                    self.check_param(method);
+                }
+            }
+            TypeKind::Tuple => {
+                for elem_type in &mut r#type.cast_mut::<TupleType>().elem_types {
+                    self.check_type(elem_type);
+                }
+            }
+            TypeKind::Optional => {
+                let inner = &mut r#type.cast_mut::<OptionalType>().inner;
+                self.check_type(inner);
+                // Types whose definition is still being checked refer back to this one and
+                // cannot be resolved yet
+                let resolved = match inner.kind() {
+                    TypeKind::Ref => self
+                        .try_canonical_type_id(inner.cast::<RefType>().type_id.unwrap())
+                        .map(|type_id| self.resolve_type(type_id)),
+                    _ => Some(inner.clone()),
+                };
+                if resolved.is_some_and(|resolved| {
+                    matches!(resolved.kind(), TypeKind::Optional | TypeKind::Void)
+                }) {
+                    panic!("{:?} cannot be made optional", inner);
+                }
+            }
+            TypeKind::Never => panic!("`never` can only be the return type of a function"),
+            _ => (),
+        }
+    }
+
+    fn check_ref_type(&mut self, ref_type: &mut RefType) {
+        if ref_type.type_id.is_none() {
+            let mut type_id = match ref_type.expr.kind() {
+                ExprKind::Ident => {
+                    let ident_expr = ref_type.expr.cast_mut::<IdentExpr>();
+                    self.symbol_table
+                        .retrieve_type(&ident_expr.ident.name)
+                        .unwrap_or_else(|| self.cannot_find_type(&ident_expr.ident.name))
+                        .type_id
+                }
+                ExprKind::Member => self.check_type_path(&ref_type.expr),
+                _ => panic!(),
+            };
+
+            if self.generic_types.contains_key(&type_id) {
+                if ref_type.type_args.is_empty() {
+                    panic!("Generic type `{:?}` needs type arguments", ref_type.expr);
+                }
+                for type_arg in &mut ref_type.type_args {
+                    self.check_type(type_arg);
+                }
+                type_id = self.instantiate_type(type_id, ref_type.type_args.clone());
+            } else if !ref_type.type_args.is_empty() {
+                panic!("Type `{:?}` does not take type arguments", ref_type.expr);
+            } else {
+                self.define_pending_type(type_id);
+            }
+            ref_type.type_id = Some(type_id);
+        }
+    }
+
+    // Qualified types such as `module.Type` or `Outer.Inner` are looked up in the namespace
+    // named by the path before the last segment
+    fn check_type_path(&self, expr: &Expr) -> u64 {
+        let member_expr = expr.cast::<MemberExpr>();
+        let name = &member_expr.member.name;
+        match self.check_namespace(&member_expr.postfix_expr) {
+            Namespace::Module(namespace) => {
+                let module = &self.module_namespaces[&namespace];
+                self.symbol_table
+                    .retrieve_module_type(name, module)
+                    .unwrap_or_else(|| {
+                        panic!("Cannot find type `{}` in module `{}`", name, namespace)
+                    })
+                    .type_id
+            }
+            Namespace::Type(owner_id) => *self
+                .nested_types
+                .get(&(owner_id, name.clone()))
+                .unwrap_or_else(|| {
+                    let nested_names = self
+                        .nested_types
+                        .keys()
+                        .filter(|(id, _)| *id == owner_id)
+                        .map(|(_, name)| name.as_str());
+                    let suggestion = similar_name(name, nested_names)
+                        .map(|similar| format!(", did you mean `{}`?", similar));
+                    panic!(
+                        "Cannot find type `{}` in `{}`{}",
+                        name,
+                        self.type_name(owner_id),
+                        suggestion.unwrap_or_default()
+                    )
+                }),
+        }
+    }
+
+    fn check_namespace(&self, expr: &Expr) -> Namespace {
+        let type_id = match expr.kind() {
+            ExprKind::Ident => {
+                let name = &expr.cast::<IdentExpr>().ident.name;
+                match self.symbol_table.retrieve_type(name) {
+                    Some(entry) => entry.type_id,
+                    None if self.module_namespaces.contains_key(name) => {
+                        return Namespace::Module(name.clone())
+                    }
+                    None if self.symbol_table.retrieve_value(name).is_some() => {
+                        panic!("`{}` is a value, not a namespace", name)
+                    }
+                    None => self.cannot_find_type(name),
+                }
+            }
+            ExprKind::Member => self.check_type_path(expr),
+            _ => panic!("{:?} is not a namespace", expr),
+        };
+        if !self.namespaces.contains(&type_id) {
+            panic!(
+                "`{:?}` is not a namespace, only modules and composite types contain types",
+                expr
+            );
+        }
+        Namespace::Type(type_id)
+    }
+
+    fn check_array_type(&mut self, array_type: &mut ArrayType) {
+        self.check_type(&mut array_type.elem_type);
+    }
+
+    fn check_func_type(&mut self, func_type: &mut FuncType) {
+        self.check_return_type(&mut func_type.return_type);
+        for param in &mut func_type.params {
+            self.check_param(param);
+        }
+        self.check_var_args(func_type);
+    }
+
+    // Only functions can be declared to never return
+    fn check_return_type(&mut self, r#type: &mut Type) {
+        if r#type.kind() != TypeKind::Never {
+            self.check_type(r#type);
+        }
+    }
+
+    fn check_var_args(&self, func_type: &FuncType) {
+        if !func_type.is_var_args || func_type.is_extern {
+            return;
+        }
+        let Some(param) = func_type.params.last() else {
+            panic!("C-style variadic functions can only be declared in `extern` blocks");
+        };
+        let r#type = self.resolve(&param.r#type);
+        if r#type.kind() != TypeKind::Array || r#type.cast::<ArrayType>().len.is_some() {
+            panic!(
+                "Variadic parameter `{}` must have a slice type `T[]`, found {:?}",
+                param.name, param.r#type
+            );
+        }
+    }
+
+    fn check_enum_type(&mut self, enum_type: &mut EnumType) {
+        for variant in &mut enum_type.variants {
+            if let Some(payload) = &mut variant.payload {
+                self.check_type(payload);
+            }
+        }
+    }
+
+    fn check_composite_type(&mut self, composite_type: &mut CompositeType) {
+        for field in &mut composite_type.fields {
+            self.check_type(&mut field.r#type);
+            if field.is_inherit && field.r#type.kind() != TypeKind::Ref {
+                panic!("Inherited field `{}` must have a named type", field.name);
+            }
+        }
+        for interface in &mut composite_type.interfaces {
+            self.check_type(interface);
+            self.interface_id(interface);
+        }
+    }
+
+    // ==================================================
+
+    fn check_ident(&mut self, ident: &mut Ident) {
+        let decl_entry = self
+            .symbol_table
+            .retrieve_value(&ident.name)
+            .unwrap_or_else(|| self.cannot_find_value(&ident.name));
+        ident.symbol_id = Some(decl_entry.value_id);
+    }
+
+    fn check_arg(&mut self, arg: &mut Arg, r#type: &Option<Type>) {
+        self.check_expr(&mut arg.expr, r#type);
+    }
+
+    fn check_param(&mut self, param: &mut Param) {
+        self.check_type(&mut param.r#type);
+    }
+
+    fn check_pattern(
+        &mut self,
+        pattern: &mut Pattern,
+        r#type: &Type,
+        bindings: &mut Vec<(String, Type)>,
+    ) {
+        let resolved_type = self.resolve(r#type);
+        match pattern {
+            Pattern::Wildcard => (),
+            Pattern::Binding(ident) => {
+                if bindings.iter().any(|(name, _)| *name == ident.name) {
+                    panic!(
+                        "Identifier `{}` is bound more than once in the same pattern",
+                        ident.name
+                    );
+                }
+                bindings.push((ident.name.clone(), r#type.clone()));
+            }
+            Pattern::IntLiteral(value) if resolved_type.kind() == TypeKind::Int => {
+                let fits = value
+                    .parse::<i128>()
+                    .is_ok_and(|value| int_range(resolved_type.cast::<IntType>()).contains(&value));
+                if !fits {
+                    panic!("Integer pattern {} does not fit in {:?}", value, r#type);
+                }
+            }
+            Pattern::BoolLiteral(_) if resolved_type.kind() == TypeKind::Bool => (),
+            Pattern::Variant(variant_pattern) if resolved_type.kind() == TypeKind::Enum => {
+                let owner_id = match variant_pattern.owner.kind() {
+                    ExprKind::Ident => {
+                        let ident = &mut variant_pattern.owner.cast_mut::<IdentExpr>().ident;
+                        let owner_id = self
+                            .symbol_table
+                            .retrieve_type(&ident.name)
+                            .unwrap_or_else(|| self.cannot_find_type(&ident.name))
+                            .type_id;
+                        ident.symbol_id = Some(owner_id);
+                        owner_id
+                    }
+                    _ => self.check_type_path(&variant_pattern.owner),
+                };
+                let expected_id = self.canonical_type_id(r#type.cast::<RefType>().type_id.unwrap());
+                // The owner of a pattern may name a generic type without type arguments
+                let is_owner = match self.instance_args.get(&expected_id) {
+                    Some((generic_id, _)) if *generic_id == owner_id => true,
+                    _ => {
+                        !self.generic_types.contains_key(&owner_id)
+                            && self.canonical_type_id(owner_id) == expected_id
+                    }
+                };
+                if !is_owner {
+                    panic!("Mismatched pattern {:?}: expected {:?}", pattern, r#type);
+                }
+
+                let enum_type = resolved_type.cast::<EnumType>();
+                let index = enum_type
+                    .variant_index(&variant_pattern.name)
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "No variant `{}` in {:?}",
+                            variant_pattern.name, variant_pattern.owner
+                        )
+                    });
+                variant_pattern.index = Some(index as u32);
+                match (
+                    &enum_type.variants[index].payload,
+                    &mut variant_pattern.payload,
+                ) {
+                    (Some(payload_type), Some(payload)) => {
+                        variant_pattern.payload_type = Some(payload_type.clone());
+                        self.check_pattern(payload, payload_type, bindings)
+                    }
+                    (None, None) => (),
+                    (Some(_), None) => panic!(
+                        "Variant `{}` carries a payload, use `{}(_)` to ignore it",
+                        variant_pattern.name, variant_pattern.name
+                    ),
+                    (None, Some(_)) => {
+                        panic!("Variant `{}` has no payload", variant_pattern.name)
+                    }
+                }
+            }
+            Pattern::Tuple(tuple_pattern) if resolved_type.kind() == TypeKind::Tuple => {
+                let elem_types = &resolved_type.cast::<TupleType>().elem_types;
+                if elem_types.len() != tuple_pattern.elems.len() {
+                    panic!(
+                        "Mismatched pattern {:?}: expected a tuple of {} elements",
+                        pattern,
+                        elem_types.len()
+                    );
+                }
+                tuple_pattern.elem_types = Some(elem_types.clone());
+                for (pattern, elem_type) in tuple_pattern.elems.iter_mut().zip(elem_types) {
+                    self.check_pattern(pattern, elem_type, bindings);
+                }
+            }
+            Pattern::Or(patterns) => {
+                let mut first_names: Option<Vec<String>> = None;
+                for pattern in patterns {
+                    let mut alt_bindings = Vec::new();
+                    self.check_pattern(pattern, r#type, &mut alt_bindings);
+                    let mut names: Vec<String> =
+                        alt_bindings.iter().map(|(name, _)| name.clone()).collect();
+                    names.sort();
+                    match &first_names {
+                        Some(first_names) if *first_names != names => panic!(
+                            "Alternatives of or-pattern {:?} must bind the same identifiers",
+                            pattern
+                        ),
+                        // A binding takes its value from whichever alternative matched
+                        Some(_) => {
+                            for (name, alt_type) in &alt_bindings {
+                                let (_, bound_type) =
+                                    bindings.iter().find(|(bound, _)| bound == name).unwrap();
+                                if self.mangle_type(alt_type) != self.mangle_type(bound_type) {
+                                    panic!(
+                                        "`{}` has type {:?} in {:?}, but {:?} in another alternative",
+                                        name, alt_type, pattern, bound_type
+                                    );
+                                }
+                            }
+                        }
+                        None => {
+                            first_names = Some(names);
+                            for (name, r#type) in alt_bindings {
+                                if bindings.iter().any(|(bound, _)| *bound == name) {
+                                    panic!(
+                                        "Identifier `{}` is bound more than once in the same pattern",
+                                        name
+                                    );
+                                }
+                                bindings.push((name, r#type));
+                            }
+                        }
+                    }
+                }
+            }
+            _ => panic!("Mismatched pattern {:?}: expected {:?}", pattern, r#type),
+        }
+    }
+
+    // ==================================================
+
+    // Operator methods are static functions taking both operands by value
+    fn check_operator_methods(&self, ident: &Ident, statics: &[Decl]) {
+        let self_name = self.mangle_type(&self.self_type(ident));
+        for decl in statics {
+            let (return_name, return_spelling) = match decl.name.as_str() {
+                "op_add" | "op_sub" | "op_mul" | "op_div" => (&self_name[..], &ident.name[..]),
+                "op_eq" | "op_lt" => ("bool", "bool"),
+                _ => continue,
+            };
+            let expected = format!("({}, {}) -> {}", self_name, self_name, return_name);
+            let r#type = decl.r#type.as_ref().unwrap();
+            if is_method(decl) || self.mangle_type(r#type) != expected {
+                panic!(
+                    "Operator method `{}` of `{}` must have type ({}, {}) -> {}, found {:?}",
+                    decl.name, ident.name, ident.name, ident.name, return_spelling, r#type
+                );
+            }
+        }
+    }
+
+    fn self_type(&self, ident: &Ident) -> Type {
+        let mut self_type = RefType::new(IdentExpr::new(ident.clone()).into());
+        self_type.type_id = ident.symbol_id;
+        self_type.into()
+    }
+
+    fn instantiate_type(&mut self, generic_id: u64, type_args: Vec<Type>) -> u64 {
+        let key = (generic_id, self.mangle_types(&type_args));
+        if let Some(type_id) = self.type_instances.get(&key) {
+            return *type_id;
+        }
+
+        let mut type_stmt = self.generic_types[&generic_id].clone();
+        if type_stmt.type_params.len() != type_args.len() {
+            panic!(
+                "Generic type `{}` takes {} type arguments, found {}",
+                type_stmt.ident.name,
+                type_stmt.type_params.len(),
+                type_args.len()
+            );
+        }
+        let name = format!("{}<{}>", type_stmt.ident.name, key.1);
+        let module = self
+            .symbol_table
+            .retrieve_type_by_id(generic_id)
+            .unwrap()
+            .module
+            .clone();
+
+        // Instances live in the scope and module of the generic definition rather than the
+        // use site
+        let outer_module = self.enter_module(module.clone());
+        let suspended = self.symbol_table.suspend_scopes();
+        let type_id = self.symbol_table.push_type(&name, &module);
+        self.type_instances.insert(key, type_id);
+        self.instance_args
+            .insert(type_id, (generic_id, type_args.clone()));
+
+        self.symbol_table.open_scope();
+        let type_params = mem::take(&mut type_stmt.type_params);
+        self.bind_type_params(&type_stmt.ident.name, &type_params, type_args.clone());
+        type_stmt.ident = Ident::new(name);
+        type_stmt.ident.symbol_id = Some(type_id);
+        self.define_type_stmt(&mut type_stmt);
+        if !self.is_collecting {
+            self.check_type_members(&mut type_stmt);
+        }
+        self.symbol_table.close_scope();
+        self.symbol_table.resume_scopes(suspended);
+        self.enter_module(outer_module);
+
+        if self.is_collecting {
+            self.deferred_instances
+                .push((type_stmt, type_params, type_args));
+        } else {
+            self.pending_stmts.push(type_stmt.into());
+        }
+        type_id
+    }
+
+    fn instantiate_func(&mut self, generic_id: u64, type_args: Vec<Type>) -> (u64, Type) {
+        let key = (generic_id, self.mangle_types(&type_args));
+        if let Some(instance) = self.func_instances.get(&key) {
+            return instance.clone();
+        }
+
+        let mut decl = self.generic_funcs[&generic_id].clone();
+        let generic_name = mem::take(&mut decl.name);
+        decl.name = format!("{}<{}>", generic_name, key.1);
+        let value_id = self.symbol_table.new_id();
+        decl.value_id = Some(value_id);
+
+        let module = self.generic_modules[&generic_id].clone();
+        let outer_module = self.enter_module(module);
+        let suspended = self.symbol_table.suspend_scopes();
+        self.symbol_table.open_scope();
+        let func_expr = decl.value.as_mut().unwrap().cast_mut::<FuncExpr>();
+        let type_params = mem::take(&mut func_expr.type_params);
+        self.bind_type_params(&generic_name, &type_params, type_args);
+        self.check_func_signature(func_expr, None);
+        let r#type = func_expr.r#type.clone().unwrap();
+        // Registered before the body is checked so that recursive calls find the instance
+        self.func_instances.insert(key, (value_id, r#type.clone()));
+        self.check_func_body(func_expr, Some(value_id));
+        self.symbol_table.close_scope();
+        self.symbol_table.resume_scopes(suspended);
+        self.enter_module(outer_module);
+
+        decl.r#type = Some(r#type.clone());
+        self.pending_stmts
+            .push(DeclStmt::new(vec![decl], false).into());
+        (value_id, r#type)
+    }
+
+    fn bind_type_params(
+        &mut self,
+        generic_name: &str,
+        type_params: &[TypeParam],
+        type_args: Vec<Type>,
+    ) {
+        for (type_param, type_arg) in type_params.iter().zip(type_args) {
+            for bound in &type_param.bounds {
+                let mut bound = bound.clone();
+                self.check_type(&mut bound);
+                let interface_id = self.interface_id(&bound);
+                if !self.implements(&type_arg, interface_id) {
+                    panic!(
+                        "Type {:?} does not implement interface {:?} required by type parameter `{}` of `{}`",
+                        type_arg, bound, type_param.ident.name, generic_name
+                    );
+                }
+            }
+
+            let mut type_stmt = TypeStmt::new(type_param.ident.clone(), type_arg);
+            self.check_type_stmt(&mut type_stmt);
+            self.pending_stmts.push(type_stmt.into());
+        }
+    }
+
+    fn interface_id(&self, r#type: &Type) -> u64 {
+        if r#type.kind() == TypeKind::Ref {
+            let type_id = self.canonical_type_id(r#type.cast::<RefType>().type_id.unwrap());
+            if self.resolve_type(type_id).kind() == TypeKind::Interface {
+                return type_id;
+            }
+        }
+        panic!("{:?} is not an interface", r#type);
+    }
+
+    // Implementations are nominal: a composite type implements the interfaces it declares,
+    // and an interface value trivially implements its own interface
+    fn implements(&self, r#type: &Type, interface_id: u64) -> bool {
+        if r#type.kind() != TypeKind::Ref {
+            return false;
+        }
+        let type_id = self.canonical_type_id(r#type.cast::<RefType>().type_id.unwrap());
+        if type_id == interface_id {
+            return true;
+        }
+        let r#type = self.resolve_type(type_id);
+        r#type.kind() == TypeKind::Composite
+            && r#type
+                .cast::<CompositeType>()
+                .interfaces
+                .iter()
+                .any(|interface| self.interface_id(interface) == interface_id)
+    }
+
+    // Every method of a declared interface needs a public method with the same signature
+    fn check_implementations(&self, type_stmt: &TypeStmt) {
+        let composite_type = type_stmt.r#type.cast::<CompositeType>();
+        for interface in &composite_type.interfaces {
+            let interface_type = self.resolve(interface);
+            for method in &interface_type.cast::<InterfaceType>().methods {
+                let decl = composite_type
+                    .get_static(&method.name)
+                    .filter(|decl| is_method(decl))
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "Type `{}` does not implement method `{}` of interface {:?}",
+                            type_stmt.ident.name, method.name, interface
+                        )
+                    });
+                if !decl.is_pub {
+                    panic!(
+                        "Method `{}` of `{}` implements interface {:?} and must be public",
+                        method.name, type_stmt.ident.name, interface
+                    );
+                }
+
+                let mut func_type = decl.r#type.as_ref().unwrap().cast::<FuncType>().clone();
+                func_type.params.remove(0);
+                if self.mangle_type(&func_type.into()) != self.mangle_type(&method.r#type) {
+                    panic!(
+                        "Method `{}` of `{}` has type {:?}, but interface {:?} requires {:?}",
+                        method.name,
+                        type_stmt.ident.name,
+                        decl.r#type.as_ref().unwrap(),
+                        interface,
+                        method.r#type
+                    );
+                }
+            }
+        }
+    }
+
+    fn mangle_types(&self, types: &[Type]) -> String {
+        let names: Vec<String> = types
+            .iter()
+            .map(|r#type| self.mangle_type(r#type))
+            .collect();
+        names.join(", ")
+    }
+
+    // Spells a type the same way wherever it is written, looking through aliases.
+    // Nominal types carry their id, as a local type may shadow a global of the same name
+    fn mangle_type(&self, r#type: &Type) -> String {
+        match r#type.kind() {
+            TypeKind::Ref => {
+                let type_id = self.canonical_type_id(r#type.cast::<RefType>().type_id.unwrap());
+                let entry = self.symbol_table.retrieve_type_by_id(type_id).unwrap();
+                let resolved_type = entry.r#type.as_ref().unwrap();
+                match resolved_type.kind() {
+                    TypeKind::Composite | TypeKind::Enum | TypeKind::Interface => {
+                        format!("{}#{}", entry.name, type_id)
+                    }
+                    _ => self.mangle_type(resolved_type),
+                }
+            }
+            TypeKind::Ptr => format!("*{}", self.mangle_type(&r#type.cast::<PtrType>().pointee)),
+            TypeKind::Optional => {
+                format!(
+                    "{}?",
+                    self.mangle_type(&r#type.cast::<OptionalType>().inner)
+                )
+            }
+            TypeKind::Array => {
+                let array_type = r#type.cast::<ArrayType>();
+                let elem_name = self.mangle_type(&array_type.elem_type);
+                match array_type.len {
+                    Some(len) => format!("{}[{}]", elem_name, len),
+                    None => format!("{}[]", elem_name),
+                }
+            }
+            TypeKind::Tuple => {
+                let elem_types = &r#type.cast::<TupleType>().elem_types;
+                match elem_types.len() {
+                    1 => format!("({},)", self.mangle_type(&elem_types[0])),
+                    _ => format!("({})", self.mangle_types(elem_types)),
+                }
+            }
+            TypeKind::Func => {
+                let func_type = r#type.cast::<FuncType>();
+                let param_types: Vec<Type> = func_type
+                    .params
+                    .iter()
+                    .map(|param| param.r#type.clone())
+                    .collect();
+                format!(
+                    "({}) -> {}",
+                    self.mangle_types(&param_types),
+                    self.mangle_type(&func_type.return_type)
+                )
+            }
+            _ => format!("{:?}", r#type),
+        }
+    }
+
+    // ==================================================
+
+    fn coerce(&self, expr: &mut Expr, r#type: &Type) {
+        if !self.try_coerce(expr, r#type) {
+            panic!(
+                "Mismatched types: expected {:?}, found {:?} of type {:?}",
+                r#type,
+                expr,
+                expr.r#type().as_ref().unwrap()
+            );
+        }
+    }
+
+    // Applies the implicit conversion from the type of `expr` to `type` if there is one,
+    // returning false if the types are not compatible
+    fn try_coerce(&self, expr: &mut Expr, r#type: &Type) -> bool {
+        let Some(expr_type) = expr.r#type().clone() else {
+            return true;
+        };
+        let is_optional = self.resolve(&expr_type).kind() == TypeKind::Optional;
+        let target_type = self.resolve(r#type);
+        match (is_optional, target_type.kind() == TypeKind::Optional) {
+            (false, true) => {
+                if !self.try_coerce(expr, &target_type.cast::<OptionalType>().inner) {
+                    return false;
+                }
+                *expr = WrapExpr::new(expr.clone(), r#type.clone()).into();
+                return true;
+            }
+            (true, false) => panic!(
+                "{:?} of type {:?} may be null, compare it with `null` or use `??` to get a {:?}",
+                expr, expr_type, r#type
+            ),
+            _ => (),
+        }
+
+        if self.is_slice_coercible(expr, r#type) {
+            let mut slice_expr = SliceExpr::new(expr.clone(), None, None);
+            slice_expr.r#type = Some(self.resolve(r#type));
+            *expr = slice_expr.into();
+            return true;
+        }
+        if target_type.kind() == TypeKind::Interface && self.coerce_to_interface(expr, r#type) {
+            return true;
+        }
+        if let Some(path) = self.upcast_path(&expr_type, r#type) {
+            *expr = UpcastExpr::new(expr.clone(), path, r#type.clone()).into();
+            return true;
+        }
+        self.mangle_type(&expr_type) == self.mangle_type(r#type)
+    }
+
+    // Pointers to a type can be used as pointers to any type it inherits from
+    fn upcast_path(&self, from: &Type, to: &Type) -> Option<Vec<u32>> {
+        if from.kind() != TypeKind::Ptr || to.kind() != TypeKind::Ptr {
+            return None;
+        }
+        let from = &from.cast::<PtrType>().pointee;
+        let to = &to.cast::<PtrType>().pointee;
+        if from.kind() != TypeKind::Ref || to.kind() != TypeKind::Ref {
+            return None;
+        }
+
+        let from_id = self.canonical_type_id(from.cast::<RefType>().type_id.unwrap());
+        let to_id = self.canonical_type_id(to.cast::<RefType>().type_id.unwrap());
+        if from_id == to_id {
+            return None;
+        }
+        self.inherit_path(from_id, to_id)
+    }
+
+    // Values and pointers of types implementing an interface become interface values
+    fn coerce_to_interface(&self, expr: &mut Expr, r#type: &Type) -> bool {
+        let mut expr_type = expr.r#type().clone().unwrap();
+        if expr_type.kind() == TypeKind::Ptr {
+            expr_type = expr_type.cast::<PtrType>().pointee.clone();
+        }
+        if expr_type.kind() != TypeKind::Ref {
+            return false;
+        }
+        let type_id = self.canonical_type_id(expr_type.cast::<RefType>().type_id.unwrap());
+        let interface_id = self.interface_id(r#type);
+        if type_id == interface_id || self.resolve_type(type_id).kind() != TypeKind::Composite {
+            return false;
+        }
+        if !self.implements(&expr_type, interface_id) {
+            panic!(
+                "{:?} of type {:?} does not implement interface {:?}",
+                expr, expr_type, r#type
+            );
+        }
+
+        let composite_type = self.resolve_composite_type(type_id);
+        let interface_type = self.resolve_type(interface_id);
+        let method_ids = interface_type
+            .cast::<InterfaceType>()
+            .methods
+            .iter()
+            .map(|method| {
+                composite_type
+                    .get_static(&method.name)
+                    .unwrap()
+                    .value_id
+                    .unwrap()
+            })
+            .collect();
+        *expr = DynExpr::new(
+            expr.clone(),
+            type_id,
+            interface_id,
+            method_ids,
+            r#type.clone(),
+        )
+        .into();
+        true
+    }
+
+    fn is_slice_coercible(&self, expr: &Expr, r#type: &Type) -> bool {
+        let r#type = self.resolve(r#type);
+        if r#type.kind() != TypeKind::Array || r#type.cast::<ArrayType>().len.is_some() {
+            return false;
+        }
+        let elem_type = self.resolve(&r#type.cast::<ArrayType>().elem_type);
+        match expr.kind() {
+            ExprKind::StrLiteral => {
+                elem_type.kind() == TypeKind::Int
+                    && matches!(elem_type.cast::<IntType>(), IntType::I8)
+            }
+            _ => {
+                let expr_type = self.resolve(expr.r#type().as_ref().unwrap());
+                expr_type.kind() == TypeKind::Array
+                    && expr_type.cast::<ArrayType>().len.is_some()
+                    && self.mangle_type(&expr_type.cast::<ArrayType>().elem_type)
+                        == self.mangle_type(&elem_type)
+            }
+        }
+    }
+
+    fn define(&mut self, symbol_id: u64, pos: Option<CursorPos>) {
+        if let Some(pos) = pos {
+            self.definitions.insert(symbol_id, pos);
+        }
+    }
+
+    // Top-level values cannot be redefined, local ones may shadow any earlier value
+    fn check_value_redefinition(&self, name: &str) {
+        if self.symbol_table.is_global_scope() {
+            if let Some(entry) = self.symbol_table.retrieve_value_same_level(name) {
+                self.redefinition(name, entry.value_id);
+            }
+        } else if self.warn_shadowing {
+            if let Some(entry) = self.symbol_table.retrieve_value(name) {
+                match self.definitions.get(&entry.value_id) {
+                    Some(pos) => eprintln!(
+                        "warning: `{}` shadows a previous definition at {:?}",
+                        name, pos
+                    ),
+                    None => eprintln!("warning: `{}` shadows a previous definition", name),
+                }
+            }
+        }
+    }
+
+    fn check_type_redefinition(&self, name: &str) {
+        if let Some(entry) = self.symbol_table.retrieve_type_same_level(name) {
+            self.redefinition(name, entry.type_id);
+        }
+    }
+
+    // Definitions without a position come from the prelude and may be replaced
+    fn redefinition(&self, name: &str, previous_id: u64) {
+        if let Some(pos) = self.definitions.get(&previous_id) {
+            panic!(
+                "`{}` is defined more than once\nnote: previous definition of `{}` is at {:?}",
+                name, name, pos
+            );
+        }
+    }
+
+    fn cannot_find_value(&self, name: &str) -> ! {
+        let other_namespace = self
+            .symbol_table
+            .retrieve_type(name)
+            .map(|_| format!(", but there is a type named `{}`", name));
+        let suggestion = similar_name(name, self.symbol_table.value_names())
+            .map(|similar| format!(", did you mean `{}`?", similar));
+        panic!(
+            "Cannot find value `{}` in this scope{}",
+            name,
+            other_namespace.or(suggestion).unwrap_or_default()
+        );
+    }
+
+    fn cannot_find_type(&self, name: &str) -> ! {
+        let other_namespace = self
+            .symbol_table
+            .retrieve_value(name)
+            .map(|_| format!(", but there is a value named `{}`", name));
+        let suggestion = similar_name(name, self.symbol_table.type_names())
+            .map(|similar| format!(", did you mean `{}`?", similar));
+        panic!(
+            "Cannot find type `{}` in this scope{}",
+            name,
+            other_namespace.or(suggestion).unwrap_or_default()
+        );
+    }
+
+    fn lookup_member(
+        &self,
+        type_id: u64,
+        name: &str,
+        is_callee: bool,
+    ) -> Option<(MemberKind, Option<u64>, Type)> {
+        let composite_type = self.resolve_composite_type(type_id);
+        let is_foreign = self.is_foreign_type(type_id);
+        let check_access = |is_pub: bool| {
+            if !is_pub && is_foreign {
+                panic!("Member `{}` is private", name);
+            }
+        };
+
+        let method = composite_type
+            .get_static(name)
+            .filter(|decl| is_method(decl));
+        if let (true, Some(decl)) = (is_callee, method) {
+            check_access(decl.is_pub);
+            return Some((
+                MemberKind::Method(Vec::new()),
+                decl.value_id,
+                decl.r#type.clone().unwrap(),
+            ));
+        }
+
+        if let Some(index) = composite_type.field_index(name) {
+            check_access(composite_type.fields[index].is_pub);
+            return Some((
+                MemberKind::Field(vec![index as u32]),
+                None,
+                composite_type.fields[index].r#type.clone(),
+            ));
+        }
+
+        match composite_type
+            .get_static(name)
+            .cloned()
+            .or_else(|| self.get_extension(type_id, name))
+        {
+            Some(decl) if is_method(&decl) && is_callee => {
+                return Some((
+                    MemberKind::Method(Vec::new()),
+                    decl.value_id,
+                    decl.r#type.unwrap(),
+                ))
+            }
+            Some(decl) if is_method(&decl) => panic!("Method `{}` must be called", name),
+            Some(_) => panic!("Static member `{}` must be accessed through its type", name),
+            None => (),
+        }
+
+        let mut found = Vec::new();
+        for (index, field) in composite_type.fields.iter().enumerate() {
+            if !field.is_inherit {
+                continue;
+            }
+            if let Some((member_kind, value_id, r#type)) = self.lookup_member(
+                field.r#type.cast::<RefType>().type_id.unwrap(),
+                name,
+                is_callee,
+            ) {
+                check_access(field.is_pub);
+                let member_kind = match member_kind {
+                    MemberKind::Field(path) => MemberKind::Field(prepend(index as u32, path)),
+                    MemberKind::Method(path) => MemberKind::Method(prepend(index as u32, path)),
+                    member_kind => member_kind,
+                };
+                found.push((field.name.clone(), (member_kind, value_id, r#type)));
+            }
+        }
+
+        if found.len() > 1 {
+            let sources: Vec<&str> = found.iter().map(|(name, _)| name.as_str()).collect();
+            panic!(
+                "Member `{}` is ambiguous: it is inherited through {}",
+                name,
+                sources.join(", ")
+            );
+        }
+        found.pop().map(|(_, member)| member)
+    }
+
+    // Static members of the type itself come before those added by extensions
+    fn get_static(&self, type_id: u64, name: &str) -> Option<Decl> {
+        let composite_type = self.resolve_composite_type(type_id);
+        match composite_type.get_static(name) {
+            Some(decl) => {
+                if !decl.is_pub && self.is_foreign_type(type_id) {
+                    let entry = self.symbol_table.retrieve_type_by_id(type_id).unwrap();
+                    panic!("Static member `{}` of `{}` is private", name, entry.name);
+                }
+                Some(decl.clone())
+            }
+            None => self.get_extension(type_id, name),
+        }
+    }
+
+    // Private extensions are only visible in the module that declares them
+    fn get_extension(&self, type_id: u64, name: &str) -> Option<Decl> {
+        let entry = self
+            .symbol_table
+            .retrieve_extension(self.canonical_type_id(type_id), name)?;
+        if !entry.decl.is_pub && entry.module != self.module_name {
+            panic!(
+                "Extension method `{}` is private to module `{}`",
+                name, entry.module
+            );
+        }
+        Some(entry.decl.clone())
+    }
+
+    fn inherit_path(&self, from_type_id: u64, to_type_id: u64) -> Option<Vec<u32>> {
+        if from_type_id == to_type_id {
+            return Some(Vec::new());
+        }
+        let r#type = self.resolve_type(from_type_id);
+        if r#type.kind() != TypeKind::Composite {
+            return None;
+        }
+
+        let mut found: Vec<(&str, Vec<u32>)> = Vec::new();
+        let composite_type = r#type.cast::<CompositeType>();
+        for (index, field) in composite_type.fields.iter().enumerate() {
+            if !field.is_inherit {
+                continue;
+            }
+            let field_type_id =
+                self.canonical_type_id(field.r#type.cast::<RefType>().type_id.unwrap());
+            if let Some(path) = self.inherit_path(field_type_id, to_type_id) {
+                found.push((&field.name, prepend(index as u32, path)));
+            }
+        }
+
+        if found.len() > 1 {
+            let sources: Vec<&str> = found.iter().map(|(name, _)| *name).collect();
+            panic!(
+                "Upcast is ambiguous: the target type is inherited through {}",
+                sources.join(", ")
+            );
+        }
+        found.pop().map(|(_, path)| path)
+    }
+
+    fn is_foreign_type(&self, type_id: u64) -> bool {
+        self.symbol_table
+            .retrieve_type_by_id(self.canonical_type_id(type_id))
+            .is_some_and(|entry| entry.module != self.module_name)
+    }
+
+    fn canonical_type_id(&self, type_id: u64) -> u64 {
+        self.try_canonical_type_id(type_id)
+            .expect("Type is used before its definition is complete")
+    }
+
+    // Follows aliases to the type they name, which is None while its definition is
+    // still being checked
+    fn try_canonical_type_id(&self, mut type_id: u64) -> Option<u64> {
+        let mut path = Vec::new();
+        loop {
+            if path.contains(&type_id) {
+                path.push(type_id);
+                panic!(
+                    "Type alias `{}` refers to itself: {}",
+                    self.type_name(type_id),
+                    self.type_path(&path[path.iter().position(|id| *id == type_id).unwrap()..])
+                );
+            }
+            path.push(type_id);
+            let r#type = self
+                .symbol_table
+                .retrieve_type_by_id(type_id)
+                .and_then(|entry| entry.r#type.as_ref())?;
+            if r#type.kind() != TypeKind::Ref {
+                return Some(type_id);
+            }
+            type_id = r#type.cast::<RefType>().type_id.unwrap();
+        }
+    }
+
+    fn type_name(&self, type_id: u64) -> &str {
+        &self.symbol_table.retrieve_type_by_id(type_id).unwrap().name
+    }
+
+    fn type_path(&self, type_ids: &[u64]) -> String {
+        let names: Vec<&str> = type_ids
+            .iter()
+            .map(|type_id| self.type_name(*type_id))
+            .collect();
+        names.join(" -> ")
+    }
+
+    // Types that contain themselves by value rather than through a pointer would need
+    // infinite storage
+    fn check_type_sizes(&mut self) {
+        let mut type_ids = mem::take(&mut self.unsized_types);
+        // Reported in the order the types were declared
+        type_ids.sort();
+        for type_id in type_ids {
+            let type_id = self.canonical_type_id(type_id);
+            let mut path = vec![type_id];
+            if self.contains_type(&self.resolve_type(type_id), type_id, &mut path) {
+                panic!(
+                    "Type `{}` has infinite size because it contains itself: {}, use a pointer to break the cycle",
+                    self.type_name(type_id),
+                    self.type_path(&path)
+                );
+            }
+        }
+    }
+
+    fn contains_type(&self, r#type: &Type, target_id: u64, path: &mut Vec<u64>) -> bool {
+        match r#type.kind() {
+            TypeKind::Ref => {
+                let type_id = self.canonical_type_id(r#type.cast::<RefType>().type_id.unwrap());
+                if type_id == target_id {
+                    path.push(type_id);
+                    return true;
+                }
+                // Other cycles are reported for the types on them
+                if path.contains(&type_id) {
+                    return false;
+                }
+                path.push(type_id);
+                if self.contains_type(&self.resolve_type(type_id), target_id, path) {
+                    return true;
+                }
+                path.pop();
+                false
+            }
+            TypeKind::Composite => r#type
+                .cast::<CompositeType>()
+                .fields
+                .iter()
+                .any(|field| self.contains_type(&field.r#type, target_id, path)),
+            TypeKind::Enum => r#type
+                .cast::<EnumType>()
+                .variants
+                .iter()
+                .filter_map(|variant| variant.payload.as_ref())
+                .any(|payload| self.contains_type(payload, target_id, path)),
+            TypeKind::Tuple => r#type
+                .cast::<TupleType>()
+                .elem_types
+                .iter()
+                .any(|elem_type| self.contains_type(elem_type, target_id, path)),
+            TypeKind::Array => {
+                let array_type = r#type.cast::<ArrayType>();
+                array_type.len.is_some()
+                    && self.contains_type(&array_type.elem_type, target_id, path)
+            }
+            TypeKind::Optional => {
+                self.contains_type(&r#type.cast::<OptionalType>().inner, target_id, path)
+            }
+            _ => false,
+        }
+    }
+
+    fn resolve_type(&self, type_id: u64) -> Type {
+        self.symbol_table
+            .retrieve_type_by_id(self.canonical_type_id(type_id))
+            .and_then(|entry| entry.r#type.clone())
+            .unwrap()
+    }
+
+    fn resolve(&self, r#type: &Type) -> Type {
+        match r#type.kind() {
+            TypeKind::Ref => self.resolve_type(r#type.cast::<RefType>().type_id.unwrap()),
+            _ => r#type.clone(),
+        }
+    }
+
+    fn resolve_composite_type(&self, type_id: u64) -> CompositeType {
+        let r#type = self.resolve_type(type_id);
+        if r#type.kind() != TypeKind::Composite {
+            panic!("{:?} is not a composite type", r#type);
+        }
+        r#type.cast::<CompositeType>().clone()
+    }
+
+    // ==================================================
+
+    fn find_witness(
+        &self,
+        rows: &[Vec<Pattern>],
+        row: &[Pattern],
+        types: &[Type],
+    ) -> Option<Vec<String>> {
+        if row.is_empty() {
+            return rows.is_empty().then(Vec::new);
+        }
+
+        if let Pattern::Or(patterns) = &row[0] {
+            return patterns.iter().find_map(|pattern| {
+                let mut row = row.to_vec();
+                row[0] = pattern.clone();
+                self.find_witness(rows, &row, types)
+            });
+        }
+
+        if let Some(constructor) = head_constructor(&row[0]) {
+            return self.find_constructor_witness(rows, row, types, &constructor);
+        }
+
+        let used: Vec<Constructor> = rows
+            .iter()
+            .flat_map(|row| head_constructors(&row[0]))
+            .collect();
+        match self.constructors(&types[0]) {
+            Some(all) if all.iter().all(|constructor| used.contains(constructor)) => {
+                all.iter().find_map(|constructor| {
+                    self.find_constructor_witness(rows, row, types, constructor)
+                })
+            }
+            all => {
+                let default_rows: Vec<Vec<Pattern>> = rows
+                    .iter()
+                    .flat_map(|row| expand_or(row))
+                    .filter(|row| head_constructor(&row[0]).is_none())
+                    .map(|row| row[1..].to_vec())
+                    .collect();
+                let mut witness = self.find_witness(&default_rows, &row[1..], &types[1..])?;
+                let head = match all.and_then(|all| all.into_iter().find(|c| !used.contains(c))) {
+                    Some(constructor) => {
+                        let sub_types = self.constructor_types(&types[0], &constructor);
+                        let wildcards = vec!["_".to_owned(); sub_types.len()];
+                        self.display_constructor(&types[0], &constructor, &wildcards)
+                    }
+                    None => "_".to_owned(),
+                };
+                witness.insert(0, head);
+                Some(witness)
+            }
+        }
+    }
+
+    fn find_constructor_witness(
+        &self,
+        rows: &[Vec<Pattern>],
+        row: &[Pattern],
+        types: &[Type],
+        constructor: &Constructor,
+    ) -> Option<Vec<String>> {
+        let mut column_types = self.constructor_types(&types[0], constructor);
+        let arity = column_types.len();
+        column_types.extend_from_slice(&types[1..]);
+
+        let rows: Vec<Vec<Pattern>> = rows
+            .iter()
+            .flat_map(|row| specialize(row, constructor, arity))
+            .collect();
+        let row = specialize(row, constructor, arity).pop()?;
+        let mut witness = self.find_witness(&rows, &row, &column_types)?;
+        let fields: Vec<String> = witness.drain(..arity).collect();
+        witness.insert(0, self.display_constructor(&types[0], constructor, &fields));
+        Some(witness)
+    }
+
+    fn constructors(&self, r#type: &Type) -> Option<Vec<Constructor>> {
+        let r#type = self.resolve(r#type);
+        match r#type.kind() {
+            TypeKind::Bool => Some(vec![Constructor::Bool(true), Constructor::Bool(false)]),
+            TypeKind::Enum => Some(
+                (0..r#type.cast::<EnumType>().variants.len())
+                    .map(|index| Constructor::Variant(index as u32))
+                    .collect(),
+            ),
+            TypeKind::Tuple => Some(vec![Constructor::Tuple]),
+            _ => None,
+        }
+    }
+
+    fn constructor_types(&self, r#type: &Type, constructor: &Constructor) -> Vec<Type> {
+        match constructor {
+            Constructor::Variant(index) => self.resolve(r#type).cast::<EnumType>().variants
+                [*index as usize]
+                .payload
+                .iter()
+                .cloned()
+                .collect(),
+            Constructor::Tuple => self.resolve(r#type).cast::<TupleType>().elem_types.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn display_constructor(
+        &self,
+        r#type: &Type,
+        constructor: &Constructor,
+        fields: &[String],
+    ) -> String {
+        match constructor {
+            Constructor::Bool(value) => value.to_string(),
+            Constructor::Int(value) => value.to_string(),
+            Constructor::Tuple if fields.len() == 1 => format!("({},)", fields[0]),
+            Constructor::Tuple => format!("({})", fields.join(", ")),
+            Constructor::Variant(index) => {
+                let type_name = &self
+                    .symbol_table
+                    .retrieve_type_by_id(r#type.cast::<RefType>().type_id.unwrap())
+                    .unwrap()
+                    .name;
+                let resolved_type = self.resolve(r#type);
+                let variant = &resolved_type.cast::<EnumType>().variants[*index as usize];
+                if fields.is_empty() {
+                    format!("{}.{}", type_name, variant.name)
+                } else {
+                    format!("{}.{}({})", type_name, variant.name, fields.join(", "))
+                }
+            }
+        }
+    }
+}
+
+fn is_generic_func(expr: &Expr) -> bool {
+    matches!(expr.kind(), ExprKind::Func) && !expr.cast::<FuncExpr>().type_params.is_empty()
+}
+
+fn type_param_name<'a>(ref_type: &RefType, type_params: &[&'a str]) -> Option<&'a str> {
+    if !matches!(ref_type.expr.kind(), ExprKind::Ident) || !ref_type.type_args.is_empty() {
+        return None;
+    }
+    let name = &ref_type.expr.cast::<IdentExpr>().ident.name;
+    type_params
+        .iter()
+        .find(|type_param| **type_param == name)
+        .copied()
+}
+
+fn mentions_type_params(r#type: &Type, type_params: &[&str]) -> bool {
+    match r#type.kind() {
+        TypeKind::Ref => {
+            let ref_type = r#type.cast::<RefType>();
+            type_param_name(ref_type, type_params).is_some()
+                || ref_type
+                    .type_args
+                    .iter()
+                    .any(|type_arg| mentions_type_params(type_arg, type_params))
+        }
+        TypeKind::Ptr => mentions_type_params(&r#type.cast::<PtrType>().pointee, type_params),
+        TypeKind::Array => mentions_type_params(&r#type.cast::<ArrayType>().elem_type, type_params),
+        TypeKind::Tuple => r#type
+            .cast::<TupleType>()
+            .elem_types
+            .iter()
+            .any(|elem_type| mentions_type_params(elem_type, type_params)),
+        TypeKind::Func => {
+            let func_type = r#type.cast::<FuncType>();
+            mentions_type_params(&func_type.return_type, type_params)
+                || func_type
+                    .params
+                    .iter()
+                    .any(|param| mentions_type_params(&param.r#type, type_params))
+        }
+        _ => false,
+    }
+}
+
+// The closest of `names` to a misspelled `name`, if any is close enough to be a typo
+fn similar_name<'a>(name: &str, names: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    names
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+// Levenshtein distance where swapping two adjacent characters counts as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![(0..=b.len()).collect::<Vec<usize>>()];
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (rows[i - 1][j] + 1)
+                .min(row[j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+    rows[a.len()][b.len()]
+}
+
+// Top-level function declarations that are registered before any body is checked
+fn is_func_decl(decl: &Decl) -> bool {
+    decl.pattern.is_none()
+        && !decl.is_mut
+        && decl
+            .value
+            .as_ref()
+            .is_some_and(|value| matches!(value.kind(), ExprKind::Func))
+}
+
+// Nested types are checked and compiled as siblings of their owner, named `Outer.Inner`
+fn hoist_nested_types(stmts: &mut Vec<Stmt>) {
+    let mut i = 0;
+    while i < stmts.len() {
+        i += 1;
+        let StmtKind::Type = stmts[i - 1].kind() else {
+            continue;
+        };
+        let type_stmt = stmts[i - 1].cast_mut::<TypeStmt>();
+        if type_stmt.r#type.kind() != TypeKind::Composite {
+            continue;
+        }
+        let types = mem::take(&mut type_stmt.r#type.cast_mut::<CompositeType>().types);
+        if let Some(nested) = types.first() {
+            if !type_stmt.type_params.is_empty() {
+                panic!(
+                    "Nested type `{}` cannot be declared in generic type `{}`",
+                    nested.ident.name, type_stmt.ident.name
+                );
+            }
+        }
+        let owner = type_stmt.ident.name.clone();
+        let nested = types.into_iter().map(|mut nested| {
+            nested.ident.name = format!("{}.{}", owner, nested.ident.name);
+            nested.into()
+        });
+        stmts.splice(i..i, nested);
+    }
+}
+
+// Declarations take effect wherever they appear, so they are never unreachable
+fn is_executable(stmt: &Stmt) -> bool {
+    match stmt.kind() {
+        StmtKind::Type | StmtKind::Extern | StmtKind::Extend => false,
+        StmtKind::Decl => !stmt.cast::<DeclStmt>().decls.iter().all(is_func_decl),
+        _ => true,
+    }
+}
+
+fn is_null_test(binary_expr: &BinaryExpr) -> bool {
+    matches!(binary_expr.lhs.kind(), ExprKind::NullLiteral)
+        || matches!(binary_expr.rhs.kind(), ExprKind::NullLiteral)
+}
+
+// Modules are named by the stem of their file
+fn module_namespace(module_name: &str) -> String {
+    Path::new(module_name)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn is_method(decl: &Decl) -> bool {
+    match &decl.value {
+        Some(value) => matches!(value.kind(), ExprKind::Func) && value.cast::<FuncExpr>().is_method,
+        None => false,
+    }
+}
+
+fn prepend(index: u32, mut path: Vec<u32>) -> Vec<u32> {
+    path.insert(0, index);
+    path
+}
+
+// Values an integer of the type can hold
+fn int_range(int_type: &IntType) -> RangeInclusive<i128> {
+    match int_type {
+        IntType::I8 => i8::MIN.into()..=i8::MAX.into(),
+        IntType::I32 => i32::MIN.into()..=i32::MAX.into(),
+        IntType::I64 => i64::MIN.into()..=i64::MAX.into(),
+    }
+}
+
+// A field of a constant composite value or tuple, reached through inherited fields
+fn const_field(expr: &Expr, path: &[u32]) -> Expr {
+    path.iter()
+        .fold(expr.clone(), |owner, &index| match owner.kind() {
+            ExprKind::Composite => {
+                let composite_expr = owner.cast::<CompositeExpr>();
+                let position = composite_expr
+                    .field_indices
+                    .iter()
+                    .position(|&field_index| field_index == index)
+                    .unwrap();
+                composite_expr.fields[position].1.clone()
+            }
+            ExprKind::Tuple => owner.cast::<TupleExpr>().elems[index as usize].clone(),
+            _ => unreachable!(),
+        })
+}
+
+fn const_elems(expr: &Expr) -> Option<Vec<Expr>> {
+    match expr.kind() {
+        ExprKind::Array => Some(expr.cast::<ArrayExpr>().elems.clone()),
+        ExprKind::ArrayRepeat => {
+            let array_repeat_expr = expr.cast::<ArrayRepeatExpr>();
+            Some(vec![
+                array_repeat_expr.value.clone();
+                array_repeat_expr.len as usize
+            ])
+        }
+        _ => None,
+    }
+}
+
+// Methods are passed their receiver before the arguments of the call
+fn is_method_call(callee: &Expr) -> bool {
+    match callee.kind() {
+        ExprKind::Member => matches!(
+            callee.cast::<MemberExpr>().member_kind,
+            Some(MemberKind::Method(_) | MemberKind::Interface(_))
+        ),
+        _ => false,
+    }
+}
+
+fn bind_pattern_ids(pattern: &mut Pattern, value_ids: &HashMap<String, u64>) {
+    match pattern {
+        Pattern::Binding(ident) => ident.symbol_id = value_ids.get(&ident.name).copied(),
+        Pattern::Variant(variant_pattern) => {
+            if let Some(payload) = &mut variant_pattern.payload {
+                bind_pattern_ids(payload, value_ids);
+            }
+        }
+        Pattern::Tuple(tuple_pattern) => {
+            for pattern in &mut tuple_pattern.elems {
+                bind_pattern_ids(pattern, value_ids);
+            }
+        }
+        Pattern::Or(patterns) => {
+            for pattern in patterns {
+                bind_pattern_ids(pattern, value_ids);
+            }
+        }
+        _ => (),
+    }
+}
+
+#[derive(Clone, Default)]
+struct InitState {
+    // Values that are unassigned on some path to the current point
+    unassigned: HashSet<u64>,
+    // Values that are assigned on some path to the current point
+    assigned: HashSet<u64>,
+}
+
+impl InitState {
+    // Joins the state at the end of another path to the same point
+    fn merge(&mut self, other: InitState) {
+        self.unassigned.extend(other.unassigned);
+        self.assigned.extend(other.assigned);
+    }
+}
+
+#[derive(Default)]
+struct EscapeAnalysis {
+    // Values declared in the function, whose storage is on its stack
+    locals: HashSet<u64>,
+    // Bindings of match arms, which share the storage of the local being matched if any
+    bindings: HashSet<u64>,
+    aliases: HashMap<u64, u64>,
+    // References flowing into a local, or out of the function, and those returned from it
+    flows: Vec<(Option<u64>, Flows)>,
+    returns: Vec<Flows>,
+    // Locals whose value outlives the function, and those whose storage does
+    escaping_values: HashSet<u64>,
+    heap_values: HashSet<u64>,
+    is_marking: bool,
+}
+
+impl EscapeAnalysis {
+    // The references in an argument may be returned by the call, and outlive the function
+    // when the callee keeps them
+    fn pass(&mut self, flows: &mut Flows, arg_flows: Flows, is_kept: bool) {
+        flows.values.extend(&arg_flows.values);
+        flows.addresses.extend(&arg_flows.addresses);
+        if is_kept {
+            self.flows.push((None, arg_flows));
+        }
+    }
+
+    fn solve(&mut self) {
+        let mut is_changed = true;
+        while is_changed {
+            is_changed = false;
+            for (value_id, flows) in &self.flows {
+                if value_id.is_some_and(|value_id| !self.escaping_values.contains(&value_id)) {
+                    continue;
+                }
+                for value_id in &flows.values {
+                    is_changed |= self.escaping_values.insert(*value_id);
+                }
+                for value_id in &flows.addresses {
+                    is_changed |= self.heap_values.insert(*value_id);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct Flows {
+    // Locals whose references are part of a value
+    values: Vec<u64>,
+    // Locals whose storage a value refers to
+    addresses: Vec<u64>,
+}
+
+enum Namespace {
+    Module(String),
+    Type(u64),
+}
+
+#[derive(Clone, PartialEq)]
+enum Constructor {
+    Variant(u32),
+    Bool(bool),
+    Int(i128),
+    Tuple,
+}
+
+fn head_constructor(pattern: &Pattern) -> Option<Constructor> {
+    match pattern {
+        Pattern::Variant(variant_pattern) => Some(Constructor::Variant(variant_pattern.index?)),
+        Pattern::BoolLiteral(value) => Some(Constructor::Bool(*value)),
+        Pattern::IntLiteral(value) => Some(Constructor::Int(value.parse().unwrap())),
+        Pattern::Tuple(_) => Some(Constructor::Tuple),
+        _ => None,
+    }
+}
+
+fn head_constructors(pattern: &Pattern) -> Vec<Constructor> {
+    match pattern {
+        Pattern::Or(patterns) => patterns.iter().flat_map(head_constructors).collect(),
+        _ => head_constructor(pattern).into_iter().collect(),
+    }
+}
+
+fn expand_or(row: &[Pattern]) -> Vec<Vec<Pattern>> {
+    match &row[0] {
+        Pattern::Or(patterns) => patterns
+            .iter()
+            .flat_map(|pattern| {
+                let mut row = row.to_vec();
+                row[0] = pattern.clone();
+                expand_or(&row)
+            })
+            .collect(),
+        _ => vec![row.to_vec()],
+    }
+}
+
+fn specialize(row: &[Pattern], constructor: &Constructor, arity: usize) -> Vec<Vec<Pattern>> {
+    expand_or(row)
+        .into_iter()
+        .filter_map(|row| {
+            let mut fields = match &row[0] {
+                Pattern::Wildcard | Pattern::Binding(_) => vec![Pattern::Wildcard; arity],
+                pattern if head_constructor(pattern).as_ref() != Some(constructor) => return None,
+                Pattern::Variant(variant_pattern) => variant_pattern
+                    .payload
+                    .iter()
+                    .map(|payload| (**payload).clone())
+                    .collect(),
+                Pattern::Tuple(tuple_pattern) => tuple_pattern.elems.clone(),
+                _ => Vec::new(),
+            };
+            fields.extend_from_slice(&row[1..]);
+            Some(fields)
+        })
+        .collect()
 }
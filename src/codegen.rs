@@ -1,42 +1,97 @@
-use std::{cell::RefCell, collections::HashMap, process::Command};
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    process::Command,
+};
 
 use inkwell::{
+    basic_block::BasicBlock,
     builder::Builder,
     context::Context,
+    intrinsics::Intrinsic,
     module::Module,
-    types::{AnyTypeEnum, BasicMetadataTypeEnum, BasicTypeEnum},
-    values::{AnyValue, AnyValueEnum, BasicValueEnum},
-    AddressSpace,
+    targets::{CodeModel, InitializationConfig, RelocMode, Target, TargetMachine},
+    types::{AnyType, AnyTypeEnum, BasicMetadataTypeEnum, BasicType, BasicTypeEnum},
+    values::{
+        AnyValue, AnyValueEnum, ArrayValue, BasicMetadataValueEnum, BasicValue, BasicValueEnum,
+        CallSiteValue, FunctionValue, GlobalValue, IntValue, PointerValue,
+    },
+    AddressSpace, IntPredicate, OptimizationLevel,
 };
 
-use crate::ast::{
-    decl::Decl,
-    expr::{
-        CallExpr, CompositeExpr, Expr, ExprKind, IdentExpr, IntLiteralExpr, MemberExpr,
-        StrLiteralExpr,
+use crate::{
+    ast::{
+        decl::Decl,
+        expr::{
+            ArrayExpr, ArrayRepeatExpr, BinaryExpr, BinaryOp, BoolLiteralExpr, CallExpr,
+            CompositeExpr, DynExpr, Expr, ExprKind, FuncExpr, IdentExpr, IfExpr, IndexExpr,
+            IntLiteralExpr, MatchExpr, MemberExpr, MemberKind, NullLiteralExpr, SliceExpr,
+            StrLiteralExpr, TryExpr, TupleExpr, UnwrapExpr, UpcastExpr, WrapExpr,
+        },
+        module_ast::ModuleAST,
+        pattern::Pattern,
+        stmt::{
+            AssignStmt, DeclStmt, ExprStmt, ExtendStmt, ExternStmt, ReturnStmt, Stmt, StmtKind,
+            TypeStmt,
+        },
+        types::{
+            ArrayType, CompositeType, EnumType, FuncType, IntType, OptionalType, PtrType, RefType,
+            TupleType, Type, TypeKind,
+        },
     },
-    module_ast::ModuleAST,
-    stmt::{DeclStmt, ExprStmt, ExternStmt, ReturnStmt, Stmt, StmtKind, TypeStmt},
-    types::{CompositeType, FuncType, IntType, PtrType, RefType, Type, TypeKind},
+    prelude::{RESULT_ERR, RESULT_OK},
 };
 
 pub struct Codegen<'ctx> {
     context: Context,
     decl_map: RefCell<HashMap<u64, (AnyTypeEnum<'ctx>, AnyValueEnum<'ctx>)>>,
     type_map: RefCell<HashMap<u64, AnyTypeEnum<'ctx>>>,
+    // Top-level type definitions are compiled on first use so that they can refer to each
+    // other in any order
+    pending_types: RefCell<HashMap<u64, Type>>,
+    stored_values: RefCell<HashSet<u64>>,
+    vtables: RefCell<HashMap<(u64, u64), GlobalValue<'ctx>>>,
+    func_depth: Cell<u32>,
+    target_machine: TargetMachine,
+    bounds_check: bool,
 }
 
 impl<'ctx> Codegen<'ctx> {
-    pub fn new() -> Self {
+    pub fn new(bounds_check: bool) -> Self {
         Self {
             context: Context::create(),
             decl_map: RefCell::new(HashMap::new()),
             type_map: RefCell::new(HashMap::new()),
+            pending_types: RefCell::new(HashMap::new()),
+            stored_values: RefCell::new(HashSet::new()),
+            vtables: RefCell::new(HashMap::new()),
+            func_depth: Cell::new(0),
+            target_machine: Self::create_target_machine(),
+            bounds_check,
         }
     }
 
+    fn create_target_machine() -> TargetMachine {
+        Target::initialize_native(&InitializationConfig::default())
+            .expect("Failed to initialize native target");
+        let triple = TargetMachine::get_default_triple();
+        Target::from_triple(&triple)
+            .unwrap()
+            .create_target_machine(
+                &triple,
+                "generic",
+                "",
+                OptimizationLevel::Default,
+                RelocMode::Default,
+                CodeModel::Default,
+            )
+            .expect("Failed to create target machine")
+    }
+
     pub fn build_module(&'ctx self, ast: &ModuleAST, output: Option<&str>) {
         let module = self.context.create_module(&ast.name);
+        module.set_triple(&self.target_machine.get_triple());
+        module.set_data_layout(&self.target_machine.get_target_data().get_data_layout());
 
         let i32_type = self.context.i32_type();
         let main_fn_type = i32_type.fn_type(&[], false);
@@ -45,10 +100,16 @@ impl<'ctx> Codegen<'ctx> {
 
         let builder = self.context.create_builder();
         builder.position_at_end(basic_block);
+        self.declare_local_types(&ast.local_types);
+        self.declare_stmts(&module, &ast.stmts);
         for stmt in &ast.stmts {
             self.build_stmt(&module, &builder, stmt);
         }
-        builder.build_return(Some(&i32_type.const_int(0, false)));
+        self.build_body_end(
+            &builder,
+            ast.is_end_reachable,
+            Some(&i32_type.const_int(0, false)),
+        );
 
         if let Some(output) = output {
             module.print_to_file(format!("{}.ll", output)).unwrap();
@@ -72,6 +133,100 @@ impl<'ctx> Codegen<'ctx> {
 
     // ==================================================
 
+    // Declares every top-level type and function before any code is built, so that they
+    // can be used before their definition
+    fn declare_stmts(&'ctx self, module: &Module<'ctx>, stmts: &[Stmt]) {
+        for stmt in stmts {
+            if let StmtKind::Type = stmt.kind() {
+                let type_stmt = stmt.cast::<TypeStmt>();
+                if !type_stmt.type_params.is_empty() {
+                    continue;
+                }
+                let type_id = type_stmt.ident.symbol_id.unwrap();
+                self.declare_type(type_id, &type_stmt.ident.name, &type_stmt.r#type);
+                self.pending_types
+                    .borrow_mut()
+                    .insert(type_id, type_stmt.r#type.clone());
+            }
+        }
+
+        for stmt in stmts {
+            match stmt.kind() {
+                StmtKind::Type => {
+                    let type_stmt = stmt.cast::<TypeStmt>();
+                    if type_stmt.type_params.is_empty()
+                        && type_stmt.r#type.kind() == TypeKind::Composite
+                    {
+                        let composite_type = type_stmt.r#type.cast::<CompositeType>();
+                        self.declare_statics(
+                            module,
+                            &type_stmt.ident.name,
+                            &composite_type.statics,
+                        );
+                    }
+                }
+                StmtKind::Extend => {
+                    let extend_stmt = stmt.cast::<ExtendStmt>();
+                    self.declare_statics(module, &extend_stmt.ident.name, &extend_stmt.statics);
+                }
+                StmtKind::Decl => self.declare_funcs(module, &stmt.cast::<DeclStmt>().decls),
+                StmtKind::Extern => {
+                    for decl_stmt in &stmt.cast::<ExternStmt>().decl_stmts {
+                        self.declare_funcs(module, &decl_stmt.decls);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    // Types declared in functions are defined where they appear, or earlier by a generic
+    // instance built at the top level that refers to them
+    fn declare_local_types(&'ctx self, type_stmts: &[TypeStmt]) {
+        for type_stmt in type_stmts {
+            let type_id = type_stmt.ident.symbol_id.unwrap();
+            self.declare_type(type_id, &type_stmt.ident.name, &type_stmt.r#type);
+            self.pending_types
+                .borrow_mut()
+                .insert(type_id, type_stmt.r#type.clone());
+        }
+    }
+
+    fn declare_statics(&'ctx self, module: &Module<'ctx>, owner_name: &str, statics: &[Decl]) {
+        for decl in statics {
+            if decl
+                .value
+                .as_ref()
+                .is_some_and(|value| matches!(value.kind(), ExprKind::Func))
+            {
+                self.declare_func(module, &format!("{}.{}", owner_name, decl.name), decl);
+            }
+        }
+    }
+
+    fn declare_funcs(&'ctx self, module: &Module<'ctx>, decls: &[Decl]) {
+        for decl in decls {
+            let is_func = decl
+                .r#type
+                .as_ref()
+                .is_some_and(|r#type| r#type.kind() == TypeKind::Func);
+            let is_func_value = decl
+                .value
+                .as_ref()
+                .is_none_or(|value| matches!(value.kind(), ExprKind::Func));
+            if decl.pattern.is_none() && is_func && is_func_value {
+                self.declare_func(module, &decl.name, decl);
+            }
+        }
+    }
+
+    fn declared_func(&self, value_id: u64) -> Option<FunctionValue<'ctx>> {
+        self.decl_map
+            .borrow()
+            .get(&value_id)
+            .map(|(_, llvm_value)| llvm_value.into_function_value())
+    }
+
     fn set_value(
         &self,
         value_id: u64,
@@ -100,7 +255,10 @@ impl<'ctx> Codegen<'ctx> {
     // ==================================================
 
     fn build_stmt(&'ctx self, module: &Module<'ctx>, builder: &Builder<'ctx>, stmt: &Stmt) {
+        self.ensure_open_block(builder);
         match stmt.kind() {
+            StmtKind::Assign => self.build_assign_stmt(module, builder, stmt.cast::<AssignStmt>()),
+            StmtKind::Extend => self.build_extend_stmt(module, builder, stmt.cast::<ExtendStmt>()),
             StmtKind::Extern => self.build_extern_stmt(module, builder, stmt.cast::<ExternStmt>()),
             StmtKind::Decl => self.build_decl_stmt(module, builder, stmt.cast::<DeclStmt>()),
             StmtKind::Expr => self.build_expr_stmt(module, builder, stmt.cast::<ExprStmt>()),
@@ -109,6 +267,22 @@ impl<'ctx> Codegen<'ctx> {
         }
     }
 
+    fn build_assign_stmt(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        assign_stmt: &AssignStmt,
+    ) {
+        let ptr = self.build_lvalue(module, builder, &assign_stmt.target);
+        let llvm_value = self.build_expr(module, builder, &assign_stmt.value);
+        let value = self.to_basic_value(
+            builder,
+            assign_stmt.value.r#type().as_ref().unwrap(),
+            llvm_value,
+        );
+        builder.build_store(ptr, value);
+    }
+
     fn build_extern_stmt(
         &'ctx self,
         module: &Module<'ctx>,
@@ -148,30 +322,11 @@ impl<'ctx> Codegen<'ctx> {
     ) {
         if let Some(expr) = &return_stmt.expr {
             let llvm_value = self.build_expr(module, builder, expr);
-            match llvm_value {
-                AnyValueEnum::ArrayValue(_) => {
-                    builder.build_return(Some(&llvm_value.into_array_value()));
-                }
-                AnyValueEnum::IntValue(_) => {
-                    builder.build_return(Some(&llvm_value.into_int_value()));
-                }
-                AnyValueEnum::FloatValue(_) => {
-                    builder.build_return(Some(&llvm_value.into_float_value()));
-                }
-                AnyValueEnum::PhiValue(_) => todo!(),
-                AnyValueEnum::FunctionValue(_) => unreachable!(),
-                AnyValueEnum::PointerValue(_) => {
-                    builder.build_return(Some(&llvm_value.into_pointer_value()));
-                }
-                AnyValueEnum::StructValue(_) => {
-                    builder.build_return(Some(&llvm_value.into_struct_value()));
-                }
-                AnyValueEnum::VectorValue(_) => {
-                    builder.build_return(Some(&llvm_value.into_vector_value()));
-                }
-                AnyValueEnum::InstructionValue(_) => unreachable!(),
-                AnyValueEnum::MetadataValue(_) => unreachable!(),
+            if expr.r#type().as_ref().unwrap().kind() == TypeKind::Never {
+                return;
             }
+            let value = self.to_basic_value(builder, expr.r#type().as_ref().unwrap(), llvm_value);
+            builder.build_return(Some(&value));
         } else {
             builder.build_return(None);
         }
@@ -183,32 +338,205 @@ impl<'ctx> Codegen<'ctx> {
         builder: &Builder<'ctx>,
         type_stmt: &TypeStmt,
     ) {
-        let llvm_type = self.compile_type(&type_stmt.r#type);
-        self.set_type(type_stmt.ident.symbol_id.unwrap(), llvm_type);
+        // Generic types are only built through their instances
+        if !type_stmt.type_params.is_empty() {
+            return;
+        }
+
+        // Types declared in functions are defined where they appear, unless used earlier
+        let type_id = type_stmt.ident.symbol_id.unwrap();
+        if !self.define_pending_type(type_id) && !self.type_map.borrow().contains_key(&type_id) {
+            self.declare_type(type_id, &type_stmt.ident.name, &type_stmt.r#type);
+            self.define_type(type_id, &type_stmt.r#type);
+        }
+
+        if type_stmt.r#type.kind() == TypeKind::Composite {
+            let composite_type = type_stmt.r#type.cast::<CompositeType>();
+            self.build_statics(
+                module,
+                builder,
+                &type_stmt.ident.name,
+                &composite_type.statics,
+            );
+        }
+    }
+
+    fn build_extend_stmt(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        extend_stmt: &ExtendStmt,
+    ) {
+        self.build_statics(
+            module,
+            builder,
+            &extend_stmt.ident.name,
+            &extend_stmt.statics,
+        );
+    }
+
+    fn build_statics(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        owner_name: &str,
+        statics: &[Decl],
+    ) {
+        let functions: Vec<Option<FunctionValue>> = statics
+            .iter()
+            .map(|decl| {
+                let name = format!("{}.{}", owner_name, decl.name);
+                match &decl.value {
+                    Some(value) if matches!(value.kind(), ExprKind::Func) => Some(
+                        self.declared_func(decl.value_id.unwrap())
+                            .unwrap_or_else(|| self.declare_func(module, &name, decl)),
+                    ),
+                    _ => {
+                        self.build_global(module, builder, &name, decl);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        for (decl, function) in statics.iter().zip(functions) {
+            if let Some(function) = function {
+                let func_expr = decl.value.as_ref().unwrap().cast::<FuncExpr>();
+                self.build_func_body(module, function, func_expr);
+            }
+        }
     }
 
     // ==================================================
 
     fn build_decl(&'ctx self, module: &Module<'ctx>, builder: &Builder<'ctx>, decl: &Decl) {
+        if let Some(pattern) = &decl.pattern {
+            self.build_decl_pattern(module, builder, pattern, decl);
+            return;
+        }
+
+        // Generic functions are only built through their instances
+        if decl.r#type.is_none() {
+            return;
+        }
+
         if decl.r#type.as_ref().unwrap().kind() == TypeKind::Func {
             self.build_func_decl(module, builder, decl);
             return;
         }
 
-        if let Some(expr) = &decl.value {
-            let llvm_type = self.compile_type(decl.r#type.as_ref().unwrap());
-            let llvm_value = self.build_expr(module, builder, expr);
-            self.set_value(decl.value_id.unwrap(), llvm_type, llvm_value);
+        // Constants declared in functions are globals as well
+        if self.func_depth.get() == 0 || decl.is_const {
+            self.build_global(module, builder, &decl.name, decl);
+            return;
+        }
+
+        let r#type = decl.r#type.as_ref().unwrap();
+        let llvm_type = self.compile_type(r#type);
+        match &decl.value {
+            Some(expr) if !decl.is_mut && !decl.is_escaping => {
+                let llvm_value = self.build_expr(module, builder, expr);
+                self.set_value(decl.value_id.unwrap(), llvm_type, llvm_value);
+            }
+            // Values declared without an initialiser are assigned later, like mutable ones
+            value => {
+                let ptr = match decl.is_escaping {
+                    true => self.build_heap_alloc(module, builder, self.to_basic_type(llvm_type)),
+                    false => builder.build_alloca(self.to_basic_type(llvm_type), &decl.name),
+                };
+                if let Some(expr) = value {
+                    let llvm_value = self.build_expr(module, builder, expr);
+                    builder.build_store(ptr, self.to_basic_value(builder, r#type, llvm_value));
+                }
+                self.stored_values
+                    .borrow_mut()
+                    .insert(decl.value_id.unwrap());
+                self.set_value(decl.value_id.unwrap(), llvm_type, ptr.into());
+            }
+        }
+    }
+
+    fn build_decl_pattern(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        pattern: &Pattern,
+        decl: &Decl,
+    ) {
+        let r#type = decl.r#type.as_ref().unwrap();
+        let llvm_value = self.build_expr(module, builder, decl.value.as_ref().unwrap());
+        self.build_pattern(builder, pattern, llvm_value, r#type, None);
+        let is_global = self.func_depth.get() == 0;
+        if !is_global && !decl.is_mut && !decl.is_escaping {
+            return;
+        }
+
+        // Globals, mutable and escaping bindings need their own storage, like plain
+        // declarations
+        for value_id in pattern.value_ids() {
+            let (llvm_type, value) = self.get_value(value_id);
+            let basic_type = self.to_basic_type(llvm_type);
+            let value = match value {
+                AnyValueEnum::PointerValue(ptr)
+                    if llvm_type.is_struct_type() || llvm_type.is_array_type() =>
+                {
+                    builder.build_load(basic_type, ptr, "")
+                }
+                _ => value.try_into().unwrap(),
+            };
+            let ptr = if is_global {
+                let global = module.add_global(basic_type, None, "");
+                global.set_initializer(&basic_type.const_zero());
+                global.as_pointer_value()
+            } else if decl.is_escaping {
+                self.build_heap_alloc(module, builder, basic_type)
+            } else {
+                builder.build_alloca(basic_type, "")
+            };
+            builder.build_store(ptr, value);
+
+            self.stored_values.borrow_mut().insert(value_id);
+            self.set_value(value_id, llvm_type, ptr.into());
         }
     }
 
     fn build_func_decl(
         &'ctx self,
         module: &Module<'ctx>,
-        builder: &Builder,
+        builder: &Builder<'ctx>,
+        decl: &Decl,
+    ) -> inkwell::values::FunctionValue {
+        match &decl.value {
+            Some(value) if matches!(value.kind(), ExprKind::Func) => {
+                let function = self
+                    .declared_func(decl.value_id.unwrap())
+                    .unwrap_or_else(|| self.declare_func(module, &decl.name, decl));
+                self.build_func_body(module, function, value.cast::<FuncExpr>());
+                function
+            }
+            Some(value) => {
+                let function = self
+                    .build_expr(module, builder, value)
+                    .into_function_value();
+                self.set_value(
+                    decl.value_id.unwrap(),
+                    function.get_type().into(),
+                    function.into(),
+                );
+                function
+            }
+            None => self
+                .declared_func(decl.value_id.unwrap())
+                .unwrap_or_else(|| self.declare_func(module, &decl.name, decl)),
+        }
+    }
+
+    fn declare_func(
+        &'ctx self,
+        module: &Module<'ctx>,
+        name: &str,
         decl: &Decl,
     ) -> inkwell::values::FunctionValue {
-        let name = &decl.name;
         let llvm_func_type =
             self.compile_func_type(decl.r#type.as_ref().unwrap().cast::<FuncType>());
         let llvm_func_value = module.add_function(name, llvm_func_type, None);
@@ -221,6 +549,107 @@ impl<'ctx> Codegen<'ctx> {
         return llvm_func_value;
     }
 
+    fn build_func_body(
+        &'ctx self,
+        module: &Module<'ctx>,
+        function: FunctionValue<'ctx>,
+        func_expr: &FuncExpr,
+    ) {
+        let builder = self.context.create_builder();
+        let basic_block = self.context.append_basic_block(function, "entry");
+        builder.position_at_end(basic_block);
+
+        for (param, llvm_param) in func_expr.params.iter().zip(function.get_param_iter()) {
+            let llvm_type = self.compile_type(&param.r#type);
+            let llvm_value = if self.is_aggregate(&param.r#type) {
+                let ptr = match param.is_escaping {
+                    true => self.build_heap_alloc(module, &builder, llvm_param.get_type()),
+                    false => builder.build_alloca(llvm_param.get_type(), &param.name),
+                };
+                builder.build_store(ptr, llvm_param);
+                ptr.as_any_value_enum()
+            } else {
+                llvm_param.as_any_value_enum()
+            };
+            self.set_value(param.value_id.unwrap(), llvm_type, llvm_value);
+        }
+
+        self.func_depth.set(self.func_depth.get() + 1);
+        for stmt in &func_expr.body {
+            self.build_stmt(module, &builder, stmt);
+        }
+        self.func_depth.set(self.func_depth.get() - 1);
+
+        self.build_body_end(&builder, func_expr.is_end_reachable, None);
+    }
+
+    // The checker only lets control reach the end of a body that has nothing to return,
+    // otherwise the current block is one that no path leads to
+    fn build_body_end(
+        &self,
+        builder: &Builder<'ctx>,
+        is_end_reachable: bool,
+        value: Option<&dyn BasicValue<'ctx>>,
+    ) {
+        if is_end_reachable {
+            builder.build_return(value);
+        } else if !self.is_terminated(builder) {
+            builder.build_unreachable();
+        }
+    }
+
+    fn is_terminated(&self, builder: &Builder<'ctx>) -> bool {
+        builder
+            .get_insert_block()
+            .unwrap()
+            .get_terminator()
+            .is_some()
+    }
+
+    // Code after a statement that never completes is built into a block without
+    // predecessors, so that the declarations among it still exist
+    fn ensure_open_block(&self, builder: &Builder<'ctx>) {
+        if self.is_terminated(builder) {
+            let function = builder.get_insert_block().unwrap().get_parent().unwrap();
+            let block = self.context.append_basic_block(function, "unreachable");
+            builder.position_at_end(block);
+        }
+    }
+
+    fn build_global(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        name: &str,
+        decl: &Decl,
+    ) {
+        let r#type = decl.r#type.as_ref().unwrap();
+        let llvm_type = self.compile_type(r#type);
+        let basic_type = self.to_basic_type(llvm_type);
+        let global = module.add_global(basic_type, None, name);
+        match &decl.value {
+            Some(expr) if decl.is_const_init => {
+                global.set_initializer(&self.build_const_expr(module, builder, expr));
+            }
+            Some(expr) => {
+                global.set_initializer(&basic_type.const_zero());
+                let llvm_value = self.build_expr(module, builder, expr);
+                let value = self.to_basic_value(builder, r#type, llvm_value);
+                builder.build_store(global.as_pointer_value(), value);
+            }
+            None => global.set_initializer(&basic_type.const_zero()),
+        }
+
+        self.stored_values
+            .borrow_mut()
+            .insert(decl.value_id.unwrap());
+        self.set_value(
+            decl.value_id.unwrap(),
+            llvm_type,
+            global.as_pointer_value().into(),
+        );
+    }
+
     // ==================================================
 
     fn build_expr(
@@ -230,9 +659,7 @@ impl<'ctx> Codegen<'ctx> {
         expr: &Expr,
     ) -> AnyValueEnum {
         match expr.kind() {
-            ExprKind::Call => self
-                .build_call_expr(module, builder, expr.cast::<CallExpr>())
-                .as_any_value_enum(),
+            ExprKind::Call => self.build_call_expr(module, builder, expr.cast::<CallExpr>()),
             ExprKind::IntLiteral => self
                 .build_int_literial_expr(module, builder, expr.cast::<IntLiteralExpr>())
                 .as_any_value_enum(),
@@ -240,10 +667,55 @@ impl<'ctx> Codegen<'ctx> {
                 .build_str_literial_expr(module, builder, expr.cast::<StrLiteralExpr>())
                 .as_any_value_enum(),
             ExprKind::Ident => self.build_ident_expr(module, builder, expr.cast::<IdentExpr>()),
-            ExprKind::Member => todo!(),
+            ExprKind::Member => self.build_member_expr(module, builder, expr.cast::<MemberExpr>()),
             ExprKind::Composite => self
                 .build_composite_expr(module, builder, expr.cast::<CompositeExpr>())
                 .as_any_value_enum(),
+            ExprKind::Array => self
+                .build_array_expr(module, builder, expr.cast::<ArrayExpr>())
+                .as_any_value_enum(),
+            ExprKind::ArrayRepeat => self
+                .build_array_repeat_expr(module, builder, expr.cast::<ArrayRepeatExpr>())
+                .as_any_value_enum(),
+            ExprKind::Index => self.build_index_expr(module, builder, expr.cast::<IndexExpr>()),
+            ExprKind::Tuple => self
+                .build_tuple_expr(module, builder, expr.cast::<TupleExpr>())
+                .as_any_value_enum(),
+            ExprKind::Slice => self
+                .build_slice_expr(module, builder, expr.cast::<SliceExpr>())
+                .as_any_value_enum(),
+            ExprKind::BoolLiteral => self
+                .context
+                .bool_type()
+                .const_int(expr.cast::<BoolLiteralExpr>().value.into(), false)
+                .as_any_value_enum(),
+            ExprKind::Match => self.build_match_expr(module, builder, expr.cast::<MatchExpr>()),
+            ExprKind::If => self.build_if_expr(module, builder, expr.cast::<IfExpr>()),
+            ExprKind::Try => self.build_try_expr(module, builder, expr.cast::<TryExpr>()),
+            ExprKind::NullLiteral => {
+                self.build_null_literal_expr(builder, expr.cast::<NullLiteralExpr>())
+            }
+            ExprKind::Wrap => self.build_wrap_expr(module, builder, expr.cast::<WrapExpr>()),
+            ExprKind::Unwrap => {
+                let unwrap_expr = expr.cast::<UnwrapExpr>();
+                let value = self.build_expr(module, builder, &unwrap_expr.expr);
+                self.build_unwrap(
+                    builder,
+                    unwrap_expr.expr.r#type().as_ref().unwrap(),
+                    unwrap_expr.r#type.as_ref().unwrap(),
+                    value,
+                )
+            }
+            ExprKind::Upcast => self
+                .build_upcast_expr(module, builder, expr.cast::<UpcastExpr>())
+                .as_any_value_enum(),
+            ExprKind::Binary => self.build_binary_expr(module, builder, expr.cast::<BinaryExpr>()),
+            ExprKind::Dyn => self
+                .build_dyn_expr(module, builder, expr.cast::<DynExpr>())
+                .as_any_value_enum(),
+            ExprKind::Func => self
+                .build_func_expr(module, builder, expr.cast::<FuncExpr>())
+                .as_any_value_enum(),
         }
     }
 
@@ -252,29 +724,185 @@ impl<'ctx> Codegen<'ctx> {
         module: &Module<'ctx>,
         builder: &Builder<'ctx>,
         call_expr: &CallExpr,
-    ) -> inkwell::values::CallSiteValue {
-        let value = self.build_expr(module, builder, &call_expr.postfix_expr);
-        let function = value.into_function_value();
-        let args: Vec<inkwell::values::BasicMetadataValueEnum> = call_expr
-            .args
-            .iter()
-            .map(|arg| {
-                let llvm_value = self.build_expr(module, builder, &arg.expr);
-                match llvm_value {
-                    AnyValueEnum::ArrayValue(_) => todo!(),
-                    AnyValueEnum::IntValue(_) => llvm_value.into_int_value().into(),
-                    AnyValueEnum::FloatValue(_) => todo!(),
-                    AnyValueEnum::PhiValue(_) => todo!(),
-                    AnyValueEnum::FunctionValue(_) => todo!(),
-                    AnyValueEnum::PointerValue(_) => llvm_value.into_pointer_value().into(),
-                    AnyValueEnum::StructValue(_) => todo!(),
-                    AnyValueEnum::VectorValue(_) => todo!(),
-                    AnyValueEnum::InstructionValue(_) => todo!(),
-                    AnyValueEnum::MetadataValue(_) => todo!(),
+    ) -> AnyValueEnum {
+        let mut args: Vec<BasicMetadataValueEnum> = Vec::new();
+        let value = match call_expr.postfix_expr.kind() {
+            ExprKind::Member => {
+                let member_expr = call_expr.postfix_expr.cast::<MemberExpr>();
+                if let Some(MemberKind::Variant(index)) = member_expr.member_kind {
+                    return self
+                        .build_variant(
+                            module,
+                            builder,
+                            call_expr.r#type.as_ref().unwrap(),
+                            index,
+                            Some(&call_expr.args[0].expr),
+                        )
+                        .as_any_value_enum();
                 }
-            })
-            .collect();
-        builder.build_call(function, &args, "")
+                if let Some(MemberKind::Method(path)) = &member_expr.member_kind {
+                    let mut receiver = self
+                        .build_expr(module, builder, &member_expr.postfix_expr)
+                        .into_pointer_value();
+                    if call_expr.is_escaping {
+                        let owner_type = self.owner_type(&member_expr.postfix_expr);
+                        let value_type = self.to_basic_type(self.compile_type(owner_type));
+                        receiver = self.build_heap_copy(module, builder, value_type, receiver);
+                    }
+                    let receiver = self.build_path_gep(
+                        builder,
+                        self.owner_type(&member_expr.postfix_expr),
+                        receiver,
+                        path,
+                        "",
+                    );
+                    args.push(receiver.into());
+                    self.get_value(member_expr.member.symbol_id.unwrap()).1
+                } else if let Some(MemberKind::Interface(index)) = member_expr.member_kind {
+                    let (data, function) =
+                        self.build_interface_method(module, builder, member_expr, index);
+                    args.push(data.into());
+                    function.as_any_value_enum()
+                } else {
+                    self.build_member_expr(module, builder, member_expr)
+                }
+            }
+            _ => self.build_expr(module, builder, &call_expr.postfix_expr),
+        };
+
+        for arg in &call_expr.args {
+            let llvm_value = self.build_expr(module, builder, &arg.expr);
+            let value =
+                self.to_basic_value(builder, arg.expr.r#type().as_ref().unwrap(), llvm_value);
+            args.push(value.into());
+        }
+
+        let call_site_value = match value {
+            AnyValueEnum::FunctionValue(function) => builder.build_call(function, &args, ""),
+            _ => {
+                // Function types may be named through an alias
+                let func_type = self
+                    .compile_type(call_expr.postfix_expr.r#type().as_ref().unwrap())
+                    .into_function_type();
+                builder.build_indirect_call(func_type, value.into_pointer_value(), &args, "")
+            }
+        };
+        // Control never comes back from a function returning never
+        if call_expr.r#type.as_ref().unwrap().kind() == TypeKind::Never {
+            builder.build_unreachable();
+        }
+
+        self.build_call_result(builder, call_site_value)
+    }
+
+    // Aggregates returned by value are spilled so that they are handled through a pointer
+    fn build_call_result(
+        &'ctx self,
+        builder: &Builder<'ctx>,
+        call_site_value: CallSiteValue<'ctx>,
+    ) -> AnyValueEnum {
+        match call_site_value.try_as_basic_value().left() {
+            Some(value) if value.is_struct_value() => {
+                let ptr = builder.build_alloca(value.get_type(), "");
+                builder.build_store(ptr, value);
+                ptr.as_any_value_enum()
+            }
+            _ => call_site_value.as_any_value_enum(),
+        }
+    }
+
+    fn build_binary_expr(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        binary_expr: &BinaryExpr,
+    ) -> AnyValueEnum {
+        if binary_expr.op == BinaryOp::Coalesce {
+            return self.build_coalesce_expr(module, builder, binary_expr);
+        }
+        if let Some(operand) = null_test_operand(binary_expr) {
+            let value = self.build_expr(module, builder, operand);
+            let is_present =
+                self.build_is_present(builder, operand.r#type().as_ref().unwrap(), value);
+            return match binary_expr.op {
+                BinaryOp::Eq => builder.build_not(is_present, "").as_any_value_enum(),
+                _ => is_present.as_any_value_enum(),
+            };
+        }
+
+        let operand_type = binary_expr.lhs.r#type().as_ref().unwrap();
+        let lhs = self.build_expr(module, builder, &binary_expr.lhs);
+        let lhs = self.to_basic_value(builder, operand_type, lhs);
+        let rhs = self.build_expr(module, builder, &binary_expr.rhs);
+        let rhs = self.to_basic_value(builder, operand_type, rhs);
+
+        if let Some(method_id) = binary_expr.method_id {
+            let function = self.get_value(method_id).1.into_function_value();
+            let args = match binary_expr.is_swapped {
+                true => [rhs.into(), lhs.into()],
+                false => [lhs.into(), rhs.into()],
+            };
+            let call_site_value = builder.build_call(function, &args, "");
+            if binary_expr.is_negated {
+                let value = call_site_value.try_as_basic_value().left().unwrap();
+                return builder
+                    .build_not(value.into_int_value(), "")
+                    .as_any_value_enum();
+            }
+            return self.build_call_result(builder, call_site_value);
+        }
+
+        let (lhs, rhs) = (lhs.into_int_value(), rhs.into_int_value());
+        let value = match binary_expr.op {
+            BinaryOp::Add => builder.build_int_add(lhs, rhs, ""),
+            BinaryOp::Sub => builder.build_int_sub(lhs, rhs, ""),
+            BinaryOp::Mul => builder.build_int_mul(lhs, rhs, ""),
+            BinaryOp::Div => builder.build_int_signed_div(lhs, rhs, ""),
+            BinaryOp::Eq => builder.build_int_compare(IntPredicate::EQ, lhs, rhs, ""),
+            BinaryOp::Ne => builder.build_int_compare(IntPredicate::NE, lhs, rhs, ""),
+            BinaryOp::Lt => builder.build_int_compare(IntPredicate::SLT, lhs, rhs, ""),
+            BinaryOp::Gt => builder.build_int_compare(IntPredicate::SGT, lhs, rhs, ""),
+            BinaryOp::Le => builder.build_int_compare(IntPredicate::SLE, lhs, rhs, ""),
+            BinaryOp::Ge => builder.build_int_compare(IntPredicate::SGE, lhs, rhs, ""),
+            BinaryOp::Coalesce => unreachable!(),
+        };
+        value.as_any_value_enum()
+    }
+
+    // The default operand is only evaluated if the optional one is null
+    fn build_coalesce_expr(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        binary_expr: &BinaryExpr,
+    ) -> AnyValueEnum {
+        let function = builder.get_insert_block().unwrap().get_parent().unwrap();
+        let optional_type = binary_expr.lhs.r#type().as_ref().unwrap();
+        let r#type = binary_expr.r#type.as_ref().unwrap();
+        let value = self.build_expr(module, builder, &binary_expr.lhs);
+        let is_present = self.build_is_present(builder, optional_type, value);
+
+        let some_block = self.context.append_basic_block(function, "coalesce.some");
+        let none_block = self.context.append_basic_block(function, "coalesce.none");
+        let end_block = self.context.append_basic_block(function, "coalesce.end");
+        builder.build_conditional_branch(is_present, some_block, none_block);
+
+        builder.position_at_end(some_block);
+        let some_value = self.build_unwrap(builder, optional_type, r#type, value);
+        let some_value = self.to_incoming_value(builder, r#type, some_value);
+        let some_block = builder.get_insert_block().unwrap();
+        builder.build_unconditional_branch(end_block);
+
+        builder.position_at_end(none_block);
+        let none_value = self.build_expr(module, builder, &binary_expr.rhs);
+        let none_value = self.to_incoming_value(builder, r#type, none_value);
+        let none_block = builder.get_insert_block().unwrap();
+        builder.build_unconditional_branch(end_block);
+
+        builder.position_at_end(end_block);
+        let phi = builder.build_phi(some_value.get_type(), "");
+        phi.add_incoming(&[(&some_value, some_block), (&none_value, none_block)]);
+        phi.as_any_value_enum()
     }
 
     fn build_str_literial_expr(
@@ -292,9 +920,171 @@ impl<'ctx> Codegen<'ctx> {
         builder: &Builder<'ctx>,
         int_literial: &IntLiteralExpr,
     ) -> inkwell::values::IntValue {
-        self.context
-            .i32_type()
-            .const_int(str::parse::<u64>(&int_literial.value).unwrap(), false)
+        self.compile_type(int_literial.r#type.as_ref().unwrap())
+            .into_int_type()
+            // Evaluated constants may be negative
+            .const_int(
+                str::parse::<i128>(&int_literial.value).unwrap() as u64,
+                false,
+            )
+    }
+
+    // Builds a value the checker evaluated at compile time, which only consists of literals
+    fn build_const_expr(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        expr: &Expr,
+    ) -> BasicValueEnum<'ctx> {
+        let llvm_type = self.to_basic_type(self.compile_type(expr.r#type().as_ref().unwrap()));
+        match expr.kind() {
+            ExprKind::IntLiteral => self
+                .build_int_literial_expr(module, builder, expr.cast::<IntLiteralExpr>())
+                .into(),
+            ExprKind::BoolLiteral => self
+                .context
+                .bool_type()
+                .const_int(expr.cast::<BoolLiteralExpr>().value.into(), false)
+                .into(),
+            ExprKind::StrLiteral => self
+                .build_str_literial_expr(module, builder, expr.cast::<StrLiteralExpr>())
+                .as_pointer_value()
+                .into(),
+            ExprKind::NullLiteral => llvm_type.const_zero(),
+            ExprKind::Wrap => {
+                let value = self.build_const_expr(module, builder, &expr.cast::<WrapExpr>().expr);
+                match llvm_type {
+                    BasicTypeEnum::StructType(struct_type) => struct_type
+                        .const_named_struct(&[
+                            self.context.bool_type().const_int(1, false).into(),
+                            value,
+                        ])
+                        .into(),
+                    _ => value,
+                }
+            }
+            ExprKind::Composite => {
+                // Fields are written in any order, but the struct is laid out by field index
+                let composite_expr = expr.cast::<CompositeExpr>();
+                let mut fields: Vec<(u32, &Expr)> = composite_expr
+                    .field_indices
+                    .iter()
+                    .copied()
+                    .zip(composite_expr.fields.iter().map(|(_, field)| field))
+                    .collect();
+                fields.sort_by_key(|(index, _)| *index);
+                let values: Vec<BasicValueEnum> = fields
+                    .into_iter()
+                    .map(|(_, field)| self.build_const_expr(module, builder, field))
+                    .collect();
+                llvm_type
+                    .into_struct_type()
+                    .const_named_struct(&values)
+                    .into()
+            }
+            ExprKind::Tuple => {
+                let values: Vec<BasicValueEnum> = expr
+                    .cast::<TupleExpr>()
+                    .elems
+                    .iter()
+                    .map(|elem| self.build_const_expr(module, builder, elem))
+                    .collect();
+                llvm_type
+                    .into_struct_type()
+                    .const_named_struct(&values)
+                    .into()
+            }
+            ExprKind::Array => {
+                let values: Vec<BasicValueEnum> = expr
+                    .cast::<ArrayExpr>()
+                    .elems
+                    .iter()
+                    .map(|elem| self.build_const_expr(module, builder, elem))
+                    .collect();
+                self.build_const_array(llvm_type.into_array_type(), &values)
+                    .into()
+            }
+            ExprKind::ArrayRepeat => {
+                let array_repeat_expr = expr.cast::<ArrayRepeatExpr>();
+                let value = self.build_const_expr(module, builder, &array_repeat_expr.value);
+                let values = vec![value; array_repeat_expr.len as usize];
+                self.build_const_array(llvm_type.into_array_type(), &values)
+                    .into()
+            }
+            // The array of a constant slice is a global of its own, which the slice may write to
+            ExprKind::Slice => {
+                let postfix_expr = &expr.cast::<SliceExpr>().postfix_expr;
+                let (ptr, len) = match postfix_expr.kind() {
+                    ExprKind::StrLiteral => {
+                        let str_literal = postfix_expr.cast::<StrLiteralExpr>();
+                        let global = self.build_str_literial_expr(module, builder, str_literal);
+                        (global.as_pointer_value(), str_literal.value.len() as u64)
+                    }
+                    _ => {
+                        let array = self
+                            .build_const_expr(module, builder, postfix_expr)
+                            .into_array_value();
+                        let global = module.add_global(array.get_type(), None, "");
+                        global.set_initializer(&array);
+                        (global.as_pointer_value(), array.get_type().len().into())
+                    }
+                };
+                let struct_type = llvm_type.into_struct_type();
+                let ptr_type = struct_type.get_field_types()[0].into_pointer_type();
+                struct_type
+                    .const_named_struct(&[
+                        ptr.const_cast(ptr_type).into(),
+                        self.context.i64_type().const_int(len, false).into(),
+                    ])
+                    .into()
+            }
+            _ => unreachable!("{:?} is not a constant", expr),
+        }
+    }
+
+    fn build_const_array(
+        &'ctx self,
+        array_type: inkwell::types::ArrayType<'ctx>,
+        values: &[BasicValueEnum<'ctx>],
+    ) -> ArrayValue<'ctx> {
+        match array_type.get_element_type() {
+            BasicTypeEnum::ArrayType(elem_type) => elem_type.const_array(
+                &values
+                    .iter()
+                    .map(|value| value.into_array_value())
+                    .collect::<Vec<_>>(),
+            ),
+            BasicTypeEnum::FloatType(elem_type) => elem_type.const_array(
+                &values
+                    .iter()
+                    .map(|value| value.into_float_value())
+                    .collect::<Vec<_>>(),
+            ),
+            BasicTypeEnum::IntType(elem_type) => elem_type.const_array(
+                &values
+                    .iter()
+                    .map(|value| value.into_int_value())
+                    .collect::<Vec<_>>(),
+            ),
+            BasicTypeEnum::PointerType(elem_type) => elem_type.const_array(
+                &values
+                    .iter()
+                    .map(|value| value.into_pointer_value())
+                    .collect::<Vec<_>>(),
+            ),
+            BasicTypeEnum::StructType(elem_type) => elem_type.const_array(
+                &values
+                    .iter()
+                    .map(|value| value.into_struct_value())
+                    .collect::<Vec<_>>(),
+            ),
+            BasicTypeEnum::VectorType(elem_type) => elem_type.const_array(
+                &values
+                    .iter()
+                    .map(|value| value.into_vector_value())
+                    .collect::<Vec<_>>(),
+            ),
+        }
     }
 
     fn build_ident_expr(
@@ -303,77 +1093,1124 @@ impl<'ctx> Codegen<'ctx> {
         builder: &Builder<'ctx>,
         ident_expr: &IdentExpr,
     ) -> AnyValueEnum {
-        let (_t, v) = self.get_value(ident_expr.ident.symbol_id.unwrap());
-        v
+        self.build_value_ref(builder, ident_expr.ident.symbol_id.unwrap())
     }
 
-    // fn build_member_expr(
-    //     &'ctx self,
-    //     module: &Module<'ctx>,
-    //     builder: &Builder<'ctx>,
-    //     member_expr: &MemberExpr,
-    // ) -> AnyTypeEnum {
-    //     let composite_value = self
-    //         .build_expr(module, builder, &member_expr.postfix_expr)
-    //         .into_struct_value();
-    // }
-
-    fn build_composite_expr(
+    fn build_member_expr(
         &'ctx self,
         module: &Module<'ctx>,
         builder: &Builder<'ctx>,
-        composite_expr: &CompositeExpr,
-    ) -> inkwell::values::PointerValue {
-        let r#type = composite_expr.r#type.as_ref().unwrap();
-        let llvm_type = match r#type.kind() {
-            TypeKind::Ref => {
-                let ref_type = r#type.cast::<RefType>();
-                self.get_type(ref_type.type_id.unwrap()).into_struct_type()
+        member_expr: &MemberExpr,
+    ) -> AnyValueEnum {
+        match member_expr.member_kind.as_ref().unwrap() {
+            MemberKind::Field(path) => {
+                let owner = self
+                    .build_expr(module, builder, &member_expr.postfix_expr)
+                    .into_pointer_value();
+                let ptr = self.build_path_gep(
+                    builder,
+                    self.owner_type(&member_expr.postfix_expr),
+                    owner,
+                    path,
+                    &member_expr.member.name,
+                );
+
+                let r#type = member_expr.r#type.as_ref().unwrap();
+                if self.is_aggregate(r#type) {
+                    ptr.as_any_value_enum()
+                } else {
+                    let basic_type = self.to_basic_type(self.compile_type(r#type));
+                    builder
+                        .build_load(basic_type, ptr, &member_expr.member.name)
+                        .as_any_value_enum()
+                }
             }
-            _ => panic!("Invalid CompositeType"),
-        };
-        let instance = builder.build_alloca(llvm_type, "");
-        for (i, (name, expr)) in composite_expr.fields.iter().enumerate() {
-            let ptr = builder
-                .build_struct_gep(llvm_type, instance, i.try_into().unwrap(), name)
-                .unwrap();
-            let llvm_value = self.build_expr(module, builder, expr);
-            let value: BasicValueEnum = match llvm_value {
-                AnyValueEnum::ArrayValue(_) => todo!(),
-                AnyValueEnum::IntValue(_) => llvm_value.into_int_value().into(),
-                AnyValueEnum::FloatValue(_) => todo!(),
-                AnyValueEnum::PhiValue(_) => todo!(),
-                AnyValueEnum::FunctionValue(_) => todo!(),
-                AnyValueEnum::PointerValue(_) => llvm_value.into_pointer_value().into(),
-                AnyValueEnum::StructValue(_) => todo!(),
-                AnyValueEnum::VectorValue(_) => todo!(),
-                AnyValueEnum::InstructionValue(_) => todo!(),
-                AnyValueEnum::MetadataValue(_) => todo!(),
-            };
-            builder.build_store(ptr, value);
+            MemberKind::Static => {
+                self.build_value_ref(builder, member_expr.member.symbol_id.unwrap())
+            }
+            MemberKind::Variant(index) => self
+                .build_variant(
+                    module,
+                    builder,
+                    member_expr.r#type.as_ref().unwrap(),
+                    *index,
+                    None,
+                )
+                .as_any_value_enum(),
+            MemberKind::Len => self
+                .build_slice_parts(module, builder, &member_expr.postfix_expr, false)
+                .1
+                .as_any_value_enum(),
+            MemberKind::Method(_) | MemberKind::Interface(_) => unreachable!(),
         }
+    }
+
+    fn build_variant(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        r#type: &Type,
+        index: u32,
+        payload: Option<&Expr>,
+    ) -> PointerValue {
+        let payload = payload.map(|expr| {
+            let payload_type = expr.r#type().as_ref().unwrap();
+            let llvm_value = self.build_expr(module, builder, expr);
+            (
+                self.to_basic_value(builder, payload_type, llvm_value),
+                payload_type,
+            )
+        });
+        self.build_variant_value(builder, r#type, index, payload)
+    }
+
+    fn build_variant_value(
+        &'ctx self,
+        builder: &Builder<'ctx>,
+        r#type: &Type,
+        index: u32,
+        payload: Option<(BasicValueEnum<'ctx>, &Type)>,
+    ) -> PointerValue {
+        let llvm_type = self.compile_type(r#type).into_struct_type();
+        let instance = builder.build_alloca(llvm_type, "");
+        let tag_ptr = builder
+            .build_struct_gep(llvm_type, instance, 0, "tag")
+            .unwrap();
+        builder.build_store(
+            tag_ptr,
+            self.context.i32_type().const_int(index.into(), false),
+        );
+
+        if let Some((value, payload_type)) = payload {
+            let payload_ptr = builder
+                .build_struct_gep(llvm_type, instance, 1, "payload")
+                .unwrap();
+            let payload_ptr = builder.build_pointer_cast(
+                payload_ptr,
+                self.compile_ptr_type(&PtrType::new(payload_type.clone())),
+                "",
+            );
+            builder.build_store(payload_ptr, value);
+        }
+
+        instance
+    }
+
+    // An `Err` is converted to the result type of the enclosing function and returned
+    fn build_try_expr(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        try_expr: &TryExpr,
+    ) -> AnyValueEnum {
+        let function = builder.get_insert_block().unwrap().get_parent().unwrap();
+        let result_type = try_expr.expr.r#type().as_ref().unwrap();
+        let ptr = self
+            .build_expr(module, builder, &try_expr.expr)
+            .into_pointer_value();
+        let tag = self.build_enum_tag(builder, result_type, ptr);
+        let is_ok = builder.build_int_compare(
+            IntPredicate::EQ,
+            tag,
+            self.context.i32_type().const_int(RESULT_OK.into(), false),
+            "",
+        );
+
+        let ok_block = self.context.append_basic_block(function, "try.ok");
+        let err_block = self.context.append_basic_block(function, "try.err");
+        builder.build_conditional_branch(is_ok, ok_block, err_block);
+
+        builder.position_at_end(err_block);
+        let err_type = try_expr.err_type.as_ref().unwrap();
+        let error = self.build_enum_payload(builder, result_type, ptr, err_type);
+        let error = self.to_basic_value(builder, err_type, error);
+        let return_type = try_expr.return_type.as_ref().unwrap();
+        let instance =
+            self.build_variant_value(builder, return_type, RESULT_ERR, Some((error, err_type)));
+        let value = self.to_basic_value(builder, return_type, instance.as_any_value_enum());
+        builder.build_return(Some(&value));
+
+        builder.position_at_end(ok_block);
+        self.build_enum_payload(builder, result_type, ptr, try_expr.r#type.as_ref().unwrap())
+    }
+
+    fn build_array_expr(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        array_expr: &ArrayExpr,
+    ) -> PointerValue {
+        let r#type = array_expr.r#type.as_ref().unwrap();
+        let llvm_type = self.compile_type(r#type).into_array_type();
+        let instance = builder.build_alloca(llvm_type, "");
+        let i32_type = self.context.i32_type();
+        for (i, elem) in array_expr.elems.iter().enumerate() {
+            let indices = [i32_type.const_zero(), i32_type.const_int(i as u64, false)];
+            let ptr = unsafe { builder.build_in_bounds_gep(llvm_type, instance, &indices, "") };
+            let llvm_value = self.build_expr(module, builder, elem);
+            let value = self.to_basic_value(builder, elem.r#type().as_ref().unwrap(), llvm_value);
+            builder.build_store(ptr, value);
+        }
+
+        instance
+    }
+
+    fn build_tuple_expr(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        tuple_expr: &TupleExpr,
+    ) -> PointerValue {
+        let r#type = tuple_expr.r#type.as_ref().unwrap();
+        let llvm_type = self.compile_type(r#type).into_struct_type();
+        let instance = builder.build_alloca(llvm_type, "");
+        for (i, elem) in tuple_expr.elems.iter().enumerate() {
+            let ptr = builder
+                .build_struct_gep(llvm_type, instance, i as u32, "")
+                .unwrap();
+            let llvm_value = self.build_expr(module, builder, elem);
+            let value = self.to_basic_value(builder, elem.r#type().as_ref().unwrap(), llvm_value);
+            builder.build_store(ptr, value);
+        }
+
+        instance
+    }
+
+    fn build_array_repeat_expr(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        array_repeat_expr: &ArrayRepeatExpr,
+    ) -> PointerValue {
+        let r#type = array_repeat_expr.r#type.as_ref().unwrap();
+        let llvm_type = self.compile_type(r#type).into_array_type();
+        let instance = builder.build_alloca(llvm_type, "");
+        if array_repeat_expr.len == 0 {
+            return instance;
+        }
+
+        let llvm_value = self.build_expr(module, builder, &array_repeat_expr.value);
+        let value = self.to_basic_value(
+            builder,
+            array_repeat_expr.value.r#type().as_ref().unwrap(),
+            llvm_value,
+        );
+
+        let i64_type = self.context.i64_type();
+        let entry_block = builder.get_insert_block().unwrap();
+        let loop_block = self
+            .context
+            .insert_basic_block_after(entry_block, "repeat.loop");
+        let end_block = self
+            .context
+            .insert_basic_block_after(loop_block, "repeat.end");
+        builder.build_unconditional_branch(loop_block);
+
+        builder.position_at_end(loop_block);
+        let index = builder.build_phi(i64_type, "");
+        let index_value = index.as_basic_value().into_int_value();
+        let ptr = unsafe {
+            builder.build_in_bounds_gep(
+                llvm_type,
+                instance,
+                &[i64_type.const_zero(), index_value],
+                "",
+            )
+        };
+        builder.build_store(ptr, value);
+        let next_index = builder.build_int_add(index_value, i64_type.const_int(1, false), "");
+        let condition = builder.build_int_compare(
+            IntPredicate::ULT,
+            next_index,
+            i64_type.const_int(array_repeat_expr.len.into(), false),
+            "",
+        );
+        builder.build_conditional_branch(condition, loop_block, end_block);
+        index.add_incoming(&[
+            (&i64_type.const_zero(), entry_block),
+            (&next_index, loop_block),
+        ]);
+
+        builder.position_at_end(end_block);
+        instance
+    }
+
+    fn build_index_expr(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        index_expr: &IndexExpr,
+    ) -> AnyValueEnum {
+        let ptr = self.build_index_ptr(module, builder, index_expr);
+        let r#type = index_expr.r#type.as_ref().unwrap();
+        if self.is_aggregate(r#type) {
+            ptr.as_any_value_enum()
+        } else {
+            let basic_type = self.to_basic_type(self.compile_type(r#type));
+            builder.build_load(basic_type, ptr, "").as_any_value_enum()
+        }
+    }
+
+    fn build_index_ptr(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        index_expr: &IndexExpr,
+    ) -> PointerValue {
+        let (ptr, len) = self.build_slice_parts(module, builder, &index_expr.postfix_expr, false);
+        let index = self.build_array_index(module, builder, &index_expr.index);
+        if self.bounds_check {
+            let condition = builder.build_int_compare(IntPredicate::UGE, index, len, "");
+            self.build_trap_if(module, builder, condition);
+        }
+
+        let elem_type = self.compile_type(index_expr.r#type.as_ref().unwrap());
+        unsafe { builder.build_in_bounds_gep(self.to_basic_type(elem_type), ptr, &[index], "") }
+    }
+
+    fn build_slice_expr(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        slice_expr: &SliceExpr,
+    ) -> PointerValue {
+        let (ptr, len) = self.build_slice_parts(
+            module,
+            builder,
+            &slice_expr.postfix_expr,
+            slice_expr.is_escaping,
+        );
+        let lo = match &slice_expr.lo {
+            Some(lo) => self.build_array_index(module, builder, lo),
+            None => self.context.i64_type().const_zero(),
+        };
+        let hi = match &slice_expr.hi {
+            Some(hi) => self.build_array_index(module, builder, hi),
+            None => len,
+        };
+        if self.bounds_check {
+            let hi_check = builder.build_int_compare(IntPredicate::UGT, hi, len, "");
+            let lo_check = builder.build_int_compare(IntPredicate::UGT, lo, hi, "");
+            let condition = builder.build_or(hi_check, lo_check, "");
+            self.build_trap_if(module, builder, condition);
+        }
+
+        let slice_type = slice_expr.r#type.as_ref().unwrap().cast::<ArrayType>();
+        let elem_type = self.to_basic_type(self.compile_type(&slice_type.elem_type));
+        let llvm_type = self.compile_slice_type(slice_type);
+        let instance = builder.build_alloca(llvm_type, "");
+        let ptr_field = builder
+            .build_struct_gep(llvm_type, instance, 0, "ptr")
+            .unwrap();
+        builder.build_store(ptr_field, unsafe {
+            builder.build_in_bounds_gep(elem_type, ptr, &[lo], "")
+        });
+        let len_field = builder
+            .build_struct_gep(llvm_type, instance, 1, "len")
+            .unwrap();
+        builder.build_store(len_field, builder.build_int_sub(hi, lo, ""));
 
         instance
     }
 
+    fn build_slice_parts(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        expr: &Expr,
+        is_escaping: bool,
+    ) -> (PointerValue<'ctx>, IntValue<'ctx>) {
+        let i64_type = self.context.i64_type();
+        if let ExprKind::StrLiteral = expr.kind() {
+            let str_literal = expr.cast::<StrLiteralExpr>();
+            let ptr = self.build_str_literial_expr(module, builder, str_literal);
+            let len = i64_type.const_int(str_literal.value.len() as u64, false);
+            return (ptr.as_pointer_value(), len);
+        }
+
+        let mut owner = self.build_expr(module, builder, expr).into_pointer_value();
+        match self.compile_type(self.owner_type(expr)) {
+            AnyTypeEnum::ArrayType(llvm_type) => {
+                // An array the slice outlives is copied to the heap first
+                if is_escaping {
+                    owner = self.build_heap_copy(module, builder, llvm_type.into(), owner);
+                }
+                let zero = i64_type.const_zero();
+                let ptr =
+                    unsafe { builder.build_in_bounds_gep(llvm_type, owner, &[zero, zero], "") };
+                (ptr, i64_type.const_int(llvm_type.len().into(), false))
+            }
+            AnyTypeEnum::StructType(llvm_type) => {
+                let ptr_field = builder.build_struct_gep(llvm_type, owner, 0, "").unwrap();
+                let ptr_type = llvm_type.get_field_type_at_index(0).unwrap();
+                let ptr = builder.build_load(ptr_type, ptr_field, "ptr");
+                let len_field = builder.build_struct_gep(llvm_type, owner, 1, "").unwrap();
+                let len = builder.build_load(i64_type, len_field, "len");
+                (ptr.into_pointer_value(), len.into_int_value())
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn build_array_index(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        index: &Expr,
+    ) -> IntValue<'ctx> {
+        let index = self.build_expr(module, builder, index).into_int_value();
+        builder.build_int_s_extend_or_bit_cast(index, self.context.i64_type(), "")
+    }
+
+    fn build_trap_if(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        condition: IntValue<'ctx>,
+    ) {
+        let current_block = builder.get_insert_block().unwrap();
+        let trap_block = self.context.insert_basic_block_after(current_block, "trap");
+        let continue_block = self.context.insert_basic_block_after(trap_block, "");
+        builder.build_conditional_branch(condition, trap_block, continue_block);
+
+        builder.position_at_end(trap_block);
+        let trap = Intrinsic::find("llvm.trap")
+            .and_then(|intrinsic| intrinsic.get_declaration(module, &[]))
+            .unwrap();
+        builder.build_call(trap, &[], "");
+        builder.build_unreachable();
+
+        builder.position_at_end(continue_block);
+    }
+
+    // Storage outliving the function that creates it comes from `malloc` and is never freed
+    fn build_heap_alloc(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        llvm_type: BasicTypeEnum<'ctx>,
+    ) -> PointerValue<'ctx> {
+        let malloc = module.get_function("malloc").unwrap_or_else(|| {
+            let i8_ptr_type = self.context.i8_type().ptr_type(AddressSpace::default());
+            let fn_type = i8_ptr_type.fn_type(&[self.context.i64_type().into()], false);
+            module.add_function("malloc", fn_type, None)
+        });
+        // The program may declare `malloc` itself with other integer and pointer types
+        let size_type = malloc.get_type().get_param_types()[0].into_int_type();
+        let size = builder.build_int_cast(llvm_type.size_of().unwrap(), size_type, "");
+        let ptr = builder
+            .build_call(malloc, &[size.into()], "")
+            .try_as_basic_value()
+            .left()
+            .unwrap()
+            .into_pointer_value();
+        builder.build_pointer_cast(ptr, llvm_type.ptr_type(AddressSpace::default()), "")
+    }
+
+    fn build_heap_copy(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        llvm_type: BasicTypeEnum<'ctx>,
+        ptr: PointerValue<'ctx>,
+    ) -> PointerValue<'ctx> {
+        let heap_ptr = self.build_heap_alloc(module, builder, llvm_type);
+        builder.build_store(heap_ptr, builder.build_load(llvm_type, ptr, ""));
+        heap_ptr
+    }
+
+    fn build_lvalue(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        expr: &Expr,
+    ) -> PointerValue {
+        match expr.kind() {
+            ExprKind::Ident => {
+                let value_id = expr.cast::<IdentExpr>().ident.symbol_id.unwrap();
+                self.get_value(value_id).1.into_pointer_value()
+            }
+            ExprKind::Member => {
+                let member_expr = expr.cast::<MemberExpr>();
+                match member_expr.member_kind.as_ref().unwrap() {
+                    MemberKind::Field(path) => {
+                        let owner = self
+                            .build_expr(module, builder, &member_expr.postfix_expr)
+                            .into_pointer_value();
+                        self.build_path_gep(
+                            builder,
+                            self.owner_type(&member_expr.postfix_expr),
+                            owner,
+                            path,
+                            &member_expr.member.name,
+                        )
+                    }
+                    _ => self
+                        .get_value(member_expr.member.symbol_id.unwrap())
+                        .1
+                        .into_pointer_value(),
+                }
+            }
+            ExprKind::Index => self.build_index_ptr(module, builder, expr.cast::<IndexExpr>()),
+            _ => unreachable!(),
+        }
+    }
+
+    fn build_match_expr(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        match_expr: &MatchExpr,
+    ) -> AnyValueEnum {
+        let function = builder.get_insert_block().unwrap().get_parent().unwrap();
+        let scrutinee_type = match_expr.expr.r#type().as_ref().unwrap();
+        let value = self.build_expr(module, builder, &match_expr.expr);
+
+        let arm_blocks: Vec<BasicBlock> = match_expr
+            .arms
+            .iter()
+            .map(|_| self.context.append_basic_block(function, "match.arm"))
+            .collect();
+        let end_block = self.context.append_basic_block(function, "match.end");
+
+        let is_switch = match_expr
+            .arms
+            .iter()
+            .all(|arm| arm.guard.is_none() && is_switchable(&arm.pattern))
+            && match_expr
+                .arms
+                .iter()
+                .any(|arm| !matches!(arm.pattern, Pattern::Wildcard | Pattern::Binding(_)));
+
+        if is_switch {
+            let discriminant = match value {
+                AnyValueEnum::PointerValue(ptr) => {
+                    self.build_enum_tag(builder, scrutinee_type, ptr)
+                }
+                _ => value.into_int_value(),
+            };
+
+            let mut cases: Vec<(IntValue, BasicBlock)> = Vec::new();
+            let mut default_block = None;
+            for (arm, arm_block) in match_expr.arms.iter().zip(&arm_blocks) {
+                for case in self.switch_cases(discriminant.get_type(), &arm.pattern) {
+                    if !cases.iter().any(|(value, _)| *value == case) {
+                        cases.push((case, *arm_block));
+                    }
+                }
+                if matches!(arm.pattern, Pattern::Wildcard | Pattern::Binding(_)) {
+                    default_block = Some(*arm_block);
+                    break;
+                }
+            }
+            let default_block = default_block.unwrap_or_else(|| {
+                let block = self
+                    .context
+                    .insert_basic_block_after(builder.get_insert_block().unwrap(), "match.default");
+                let current_block = builder.get_insert_block().unwrap();
+                builder.position_at_end(block);
+                builder.build_unreachable();
+                builder.position_at_end(current_block);
+                block
+            });
+            builder.build_switch(discriminant, default_block, &cases);
+        }
+
+        let r#type = match_expr.r#type.as_ref().unwrap();
+        let mut incoming: Vec<(BasicValueEnum, BasicBlock)> = Vec::new();
+        for (arm, arm_block) in match_expr.arms.iter().zip(&arm_blocks) {
+            let mut next_block = None;
+            if is_switch {
+                builder.position_at_end(*arm_block);
+                self.build_pattern(builder, &arm.pattern, value, scrutinee_type, None);
+            } else {
+                let block = self
+                    .context
+                    .insert_basic_block_after(builder.get_insert_block().unwrap(), "match.next");
+                self.build_pattern(builder, &arm.pattern, value, scrutinee_type, Some(block));
+                match &arm.guard {
+                    Some(guard) => {
+                        let condition = self.build_expr(module, builder, guard).into_int_value();
+                        builder.build_conditional_branch(condition, *arm_block, block);
+                    }
+                    None => {
+                        builder.build_unconditional_branch(*arm_block);
+                    }
+                }
+                builder.position_at_end(*arm_block);
+                next_block = Some(block);
+            }
+
+            let llvm_value = self.build_expr(module, builder, &arm.body);
+            // An arm that never completes does not reach the end of the match
+            if !self.is_terminated(builder) {
+                if !matches!(r#type.kind(), TypeKind::Void | TypeKind::Never) {
+                    let value = self.to_incoming_value(builder, r#type, llvm_value);
+                    incoming.push((value, builder.get_insert_block().unwrap()));
+                }
+                builder.build_unconditional_branch(end_block);
+            }
+
+            if let Some(next_block) = next_block {
+                builder.position_at_end(next_block);
+            }
+        }
+        if !is_switch {
+            builder.build_unreachable();
+        }
+
+        builder.position_at_end(end_block);
+        let Some((first, _)) = incoming.first() else {
+            return self.context.i8_type().get_undef().as_any_value_enum();
+        };
+        let phi = builder.build_phi(first.get_type(), "");
+        for (value, block) in &incoming {
+            phi.add_incoming(&[(value, *block)]);
+        }
+        phi.as_any_value_enum()
+    }
+
+    fn build_if_expr(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        if_expr: &IfExpr,
+    ) -> AnyValueEnum {
+        let function = builder.get_insert_block().unwrap().get_parent().unwrap();
+        let cond = self
+            .build_expr(module, builder, &if_expr.cond)
+            .into_int_value();
+        let then_block = self.context.append_basic_block(function, "if.then");
+        let else_block = self.context.append_basic_block(function, "if.else");
+        let end_block = self.context.append_basic_block(function, "if.end");
+        builder.build_conditional_branch(cond, then_block, else_block);
+
+        let r#type = if_expr.r#type.as_ref().unwrap();
+        let else_body = if_expr.else_body.as_deref().unwrap_or_default();
+        let mut incoming: Vec<(BasicValueEnum, BasicBlock)> = Vec::new();
+        for (body, block) in [
+            (&if_expr.then_body[..], then_block),
+            (else_body, else_block),
+        ] {
+            builder.position_at_end(block);
+            let llvm_value = self.build_block(module, builder, body);
+            // A branch that never completes does not reach the end of the if
+            if self.is_terminated(builder) {
+                continue;
+            }
+            if let (Some(llvm_value), false) = (llvm_value, r#type.kind() == TypeKind::Void) {
+                let value = self.to_incoming_value(builder, r#type, llvm_value);
+                incoming.push((value, builder.get_insert_block().unwrap()));
+            }
+            builder.build_unconditional_branch(end_block);
+        }
+
+        builder.position_at_end(end_block);
+        let Some((first, _)) = incoming.first() else {
+            return self.context.i8_type().get_undef().as_any_value_enum();
+        };
+        let phi = builder.build_phi(first.get_type(), "");
+        for (value, block) in &incoming {
+            phi.add_incoming(&[(value, *block)]);
+        }
+        phi.as_any_value_enum()
+    }
+
+    // Builds a list of statements and returns the value of a trailing expression
+    fn build_block(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        stmts: &[Stmt],
+    ) -> Option<AnyValueEnum> {
+        let (last, init) = stmts.split_last()?;
+        for stmt in init {
+            self.build_stmt(module, builder, stmt);
+        }
+        match last.kind() {
+            StmtKind::Expr => {
+                self.ensure_open_block(builder);
+                Some(self.build_expr(module, builder, &last.cast::<ExprStmt>().expr))
+            }
+            _ => {
+                self.build_stmt(module, builder, last);
+                None
+            }
+        }
+    }
+
+    fn switch_cases(
+        &self,
+        int_type: inkwell::types::IntType<'ctx>,
+        pattern: &Pattern,
+    ) -> Vec<IntValue<'ctx>> {
+        match pattern {
+            Pattern::IntLiteral(value) => {
+                vec![int_type.const_int(value.parse::<i64>().unwrap() as u64, true)]
+            }
+            Pattern::BoolLiteral(value) => vec![int_type.const_int((*value).into(), false)],
+            Pattern::Variant(variant_pattern) => {
+                vec![int_type.const_int(variant_pattern.index.unwrap().into(), false)]
+            }
+            Pattern::Or(patterns) => patterns
+                .iter()
+                .flat_map(|pattern| self.switch_cases(int_type, pattern))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    // Binds the pattern against `value`. When `fail_block` is given the pattern is also
+    // tested, branching there on mismatch and leaving the builder in the matching path.
+    fn build_pattern(
+        &'ctx self,
+        builder: &Builder<'ctx>,
+        pattern: &Pattern,
+        value: AnyValueEnum<'ctx>,
+        r#type: &Type,
+        fail_block: Option<BasicBlock<'ctx>>,
+    ) {
+        match pattern {
+            Pattern::Wildcard => (),
+            Pattern::Binding(ident) => {
+                self.set_value(ident.symbol_id.unwrap(), self.compile_type(r#type), value)
+            }
+            Pattern::IntLiteral(_) | Pattern::BoolLiteral(_) => {
+                if let Some(fail_block) = fail_block {
+                    let value = value.into_int_value();
+                    let case = self.switch_cases(value.get_type(), pattern)[0];
+                    let condition = builder.build_int_compare(IntPredicate::EQ, value, case, "");
+                    self.build_pattern_branch(builder, condition, fail_block);
+                }
+            }
+            Pattern::Variant(variant_pattern) => {
+                let ptr = value.into_pointer_value();
+                if let Some(fail_block) = fail_block {
+                    let tag = self.build_enum_tag(builder, r#type, ptr);
+                    let case = self.switch_cases(tag.get_type(), pattern)[0];
+                    let condition = builder.build_int_compare(IntPredicate::EQ, tag, case, "");
+                    self.build_pattern_branch(builder, condition, fail_block);
+                }
+                if let Some(payload) = &variant_pattern.payload {
+                    let payload_type = variant_pattern.payload_type.as_ref().unwrap();
+                    let payload_value = self.build_enum_payload(builder, r#type, ptr, payload_type);
+                    self.build_pattern(builder, payload, payload_value, payload_type, fail_block);
+                }
+            }
+            Pattern::Tuple(tuple_pattern) => {
+                let ptr = value.into_pointer_value();
+                let llvm_type = self.compile_type(r#type).into_struct_type();
+                let elem_types = tuple_pattern.elem_types.as_ref().unwrap();
+                for (i, (pattern, elem_type)) in
+                    tuple_pattern.elems.iter().zip(elem_types).enumerate()
+                {
+                    let elem_ptr = builder
+                        .build_struct_gep(llvm_type, ptr, i as u32, "")
+                        .unwrap();
+                    let elem_value = if self.is_aggregate(elem_type) {
+                        elem_ptr.as_any_value_enum()
+                    } else {
+                        let elem_llvm_type = self.to_basic_type(self.compile_type(elem_type));
+                        builder
+                            .build_load(elem_llvm_type, elem_ptr, "")
+                            .as_any_value_enum()
+                    };
+                    self.build_pattern(builder, pattern, elem_value, elem_type, fail_block);
+                }
+            }
+            // In an irrefutable pattern the last alternative is the one left when the others
+            // fail, so it is not tested
+            Pattern::Or(patterns) => {
+                let value_ids = pattern.value_ids();
+                let matched_block = self
+                    .context
+                    .insert_basic_block_after(builder.get_insert_block().unwrap(), "match.or");
+                let mut incoming: Vec<Vec<(BasicValueEnum, BasicBlock)>> =
+                    vec![Vec::new(); value_ids.len()];
+                for (i, pattern) in patterns.iter().enumerate() {
+                    let next_block = if i == patterns.len() - 1 {
+                        fail_block
+                    } else {
+                        Some(self.context.insert_basic_block_after(
+                            builder.get_insert_block().unwrap(),
+                            "match.alt",
+                        ))
+                    };
+                    self.build_pattern(builder, pattern, value, r#type, next_block);
+
+                    let block = builder.get_insert_block().unwrap();
+                    for (values, value_id) in incoming.iter_mut().zip(&value_ids) {
+                        let value = self.get_value(*value_id).1.try_into().unwrap();
+                        values.push((value, block));
+                    }
+                    builder.build_unconditional_branch(matched_block);
+                    if let (Some(next_block), true) = (next_block, next_block != fail_block) {
+                        builder.position_at_end(next_block);
+                    }
+                }
+
+                builder.position_at_end(matched_block);
+                for (value_id, values) in value_ids.into_iter().zip(incoming) {
+                    let (llvm_type, _) = self.get_value(value_id);
+                    let phi = builder.build_phi(values[0].0.get_type(), "");
+                    for (value, block) in &values {
+                        phi.add_incoming(&[(value, *block)]);
+                    }
+                    self.set_value(value_id, llvm_type, phi.as_any_value_enum());
+                }
+            }
+        }
+    }
+
+    fn build_pattern_branch(
+        &self,
+        builder: &Builder<'ctx>,
+        condition: IntValue<'ctx>,
+        fail_block: BasicBlock<'ctx>,
+    ) {
+        let matched_block = self
+            .context
+            .insert_basic_block_after(builder.get_insert_block().unwrap(), "match.test");
+        builder.build_conditional_branch(condition, matched_block, fail_block);
+        builder.position_at_end(matched_block);
+    }
+
+    fn build_enum_tag(
+        &'ctx self,
+        builder: &Builder<'ctx>,
+        r#type: &Type,
+        ptr: PointerValue<'ctx>,
+    ) -> IntValue<'ctx> {
+        let llvm_type = self.compile_type(r#type).into_struct_type();
+        let tag_ptr = builder.build_struct_gep(llvm_type, ptr, 0, "tag").unwrap();
+        builder
+            .build_load(self.context.i32_type(), tag_ptr, "")
+            .into_int_value()
+    }
+
+    fn build_enum_payload(
+        &'ctx self,
+        builder: &Builder<'ctx>,
+        r#type: &Type,
+        ptr: PointerValue<'ctx>,
+        payload_type: &Type,
+    ) -> AnyValueEnum<'ctx> {
+        let llvm_type = self.compile_type(r#type).into_struct_type();
+        let payload_ptr = builder
+            .build_struct_gep(llvm_type, ptr, 1, "payload")
+            .unwrap();
+        let payload_ptr = builder.build_pointer_cast(
+            payload_ptr,
+            self.compile_ptr_type(&PtrType::new(payload_type.clone())),
+            "",
+        );
+        if self.is_aggregate(payload_type) {
+            payload_ptr.as_any_value_enum()
+        } else {
+            let basic_type = self.to_basic_type(self.compile_type(payload_type));
+            builder
+                .build_load(basic_type, payload_ptr, "")
+                .as_any_value_enum()
+        }
+    }
+
+    fn build_upcast_expr(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        upcast_expr: &UpcastExpr,
+    ) -> inkwell::values::PointerValue {
+        let ptr = self
+            .build_expr(module, builder, &upcast_expr.expr)
+            .into_pointer_value();
+        self.build_path_gep(
+            builder,
+            self.owner_type(&upcast_expr.expr),
+            ptr,
+            &upcast_expr.path,
+            "",
+        )
+    }
+
+    fn build_dyn_expr(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        dyn_expr: &DynExpr,
+    ) -> PointerValue {
+        let llvm_type = self
+            .compile_type(dyn_expr.r#type.as_ref().unwrap())
+            .into_struct_type();
+        let mut ptr = self
+            .build_expr(module, builder, &dyn_expr.expr)
+            .into_pointer_value();
+        if dyn_expr.is_escaping {
+            let value_type = self.to_basic_type(self.compile_type(self.owner_type(&dyn_expr.expr)));
+            ptr = self.build_heap_copy(module, builder, value_type, ptr);
+        }
+        let data = builder.build_pointer_cast(
+            ptr,
+            self.context.i8_type().ptr_type(AddressSpace::default()),
+            "",
+        );
+        let vtable = builder.build_pointer_cast(
+            self.get_vtable(module, dyn_expr).as_pointer_value(),
+            llvm_type.get_field_types()[1].into_pointer_type(),
+            "",
+        );
+
+        let instance = builder.build_alloca(llvm_type, "");
+        let data_ptr = builder
+            .build_struct_gep(llvm_type, instance, 0, "")
+            .unwrap();
+        builder.build_store(data_ptr, data);
+        let vtable_ptr = builder
+            .build_struct_gep(llvm_type, instance, 1, "")
+            .unwrap();
+        builder.build_store(vtable_ptr, vtable);
+        instance
+    }
+
+    // Each implementation shares a single constant vtable
+    fn get_vtable(&'ctx self, module: &Module<'ctx>, dyn_expr: &DynExpr) -> GlobalValue<'ctx> {
+        let key = (dyn_expr.type_id, dyn_expr.interface_id);
+        if let Some(vtable) = self.vtables.borrow().get(&key) {
+            return *vtable;
+        }
+
+        let i8_ptr_type = self.context.i8_type().ptr_type(AddressSpace::default());
+        let methods: Vec<PointerValue> = dyn_expr
+            .method_ids
+            .iter()
+            .map(|value_id| {
+                let function = self.get_value(*value_id).1.into_function_value();
+                function
+                    .as_global_value()
+                    .as_pointer_value()
+                    .const_cast(i8_ptr_type)
+            })
+            .collect();
+        let vtable_type = i8_ptr_type.array_type(methods.len() as u32);
+        let vtable = module.add_global(vtable_type, None, "vtable");
+        vtable.set_constant(true);
+        vtable.set_initializer(&i8_ptr_type.const_array(&methods));
+        self.vtables.borrow_mut().insert(key, vtable);
+        vtable
+    }
+
+    fn build_interface_method(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        member_expr: &MemberExpr,
+        index: u32,
+    ) -> (BasicValueEnum, PointerValue) {
+        let llvm_type = self
+            .compile_type(self.owner_type(&member_expr.postfix_expr))
+            .into_struct_type();
+        let owner = self
+            .build_expr(module, builder, &member_expr.postfix_expr)
+            .into_pointer_value();
+        let data_type = llvm_type.get_field_types()[0];
+        let data_ptr = builder.build_struct_gep(llvm_type, owner, 0, "").unwrap();
+        let data = builder.build_load(data_type, data_ptr, "data");
+        let vtable_type = llvm_type.get_field_types()[1];
+        let vtable_ptr = builder.build_struct_gep(llvm_type, owner, 1, "").unwrap();
+        let vtable = builder
+            .build_load(vtable_type, vtable_ptr, "vtable")
+            .into_pointer_value();
+
+        let i8_ptr_type = self.context.i8_type().ptr_type(AddressSpace::default());
+        let index = self.context.i64_type().const_int(index.into(), false);
+        let method_ptr = unsafe { builder.build_in_bounds_gep(i8_ptr_type, vtable, &[index], "") };
+        let method = builder
+            .build_load(i8_ptr_type, method_ptr, &member_expr.member.name)
+            .into_pointer_value();
+        let func_type = member_expr.r#type.as_ref().unwrap().cast::<FuncType>();
+        let method = builder.build_pointer_cast(
+            method,
+            self.compile_func_type(func_type)
+                .ptr_type(AddressSpace::default()),
+            "",
+        );
+        (data, method)
+    }
+
+    fn build_null_literal_expr(
+        &'ctx self,
+        builder: &Builder<'ctx>,
+        null_literal: &NullLiteralExpr,
+    ) -> AnyValueEnum {
+        let llvm_type =
+            self.to_basic_type(self.compile_type(null_literal.r#type.as_ref().unwrap()));
+        if let BasicTypeEnum::PointerType(ptr_type) = llvm_type {
+            return ptr_type.const_null().as_any_value_enum();
+        }
+        let ptr = builder.build_alloca(llvm_type, "");
+        builder.build_store(ptr, llvm_type.const_zero());
+        ptr.as_any_value_enum()
+    }
+
+    fn build_wrap_expr(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        wrap_expr: &WrapExpr,
+    ) -> AnyValueEnum {
+        let llvm_value = self.build_expr(module, builder, &wrap_expr.expr);
+        let value = self.to_basic_value(
+            builder,
+            wrap_expr.expr.r#type().as_ref().unwrap(),
+            llvm_value,
+        );
+        let llvm_type = self.compile_type(wrap_expr.r#type.as_ref().unwrap());
+        if !llvm_type.is_struct_type() {
+            return value.as_any_value_enum();
+        }
+
+        let struct_type = llvm_type.into_struct_type();
+        let ptr = builder.build_alloca(struct_type, "");
+        let present_ptr = builder.build_struct_gep(struct_type, ptr, 0, "").unwrap();
+        builder.build_store(present_ptr, self.context.bool_type().const_int(1, false));
+        let value_ptr = builder.build_struct_gep(struct_type, ptr, 1, "").unwrap();
+        builder.build_store(value_ptr, value);
+        ptr.as_any_value_enum()
+    }
+
+    fn build_unwrap(
+        &'ctx self,
+        builder: &Builder<'ctx>,
+        optional_type: &Type,
+        inner_type: &Type,
+        value: AnyValueEnum<'ctx>,
+    ) -> AnyValueEnum {
+        let llvm_type = self.compile_type(optional_type);
+        if !llvm_type.is_struct_type() {
+            return value;
+        }
+
+        let struct_type = llvm_type.into_struct_type();
+        let ptr = builder
+            .build_struct_gep(struct_type, value.into_pointer_value(), 1, "")
+            .unwrap();
+        if self.is_aggregate(inner_type) {
+            return ptr.as_any_value_enum();
+        }
+        let inner_llvm_type = self.to_basic_type(self.compile_type(inner_type));
+        builder
+            .build_load(inner_llvm_type, ptr, "")
+            .as_any_value_enum()
+    }
+
+    fn build_is_present(
+        &'ctx self,
+        builder: &Builder<'ctx>,
+        optional_type: &Type,
+        value: AnyValueEnum<'ctx>,
+    ) -> IntValue {
+        let llvm_type = self.compile_type(optional_type);
+        if !llvm_type.is_struct_type() {
+            return builder.build_is_not_null(value.into_pointer_value(), "");
+        }
+
+        let struct_type = llvm_type.into_struct_type();
+        let ptr = builder
+            .build_struct_gep(struct_type, value.into_pointer_value(), 0, "")
+            .unwrap();
+        builder
+            .build_load(self.context.bool_type(), ptr, "")
+            .into_int_value()
+    }
+
+    fn build_composite_expr(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        composite_expr: &CompositeExpr,
+    ) -> inkwell::values::PointerValue {
+        let r#type = composite_expr.r#type.as_ref().unwrap();
+        let llvm_type = match r#type.kind() {
+            TypeKind::Ref => self.compile_type(r#type).into_struct_type(),
+            _ => panic!("Invalid CompositeType"),
+        };
+        let instance = builder.build_alloca(llvm_type, "");
+        let fields = composite_expr.fields.iter();
+        for ((name, expr), index) in fields.zip(&composite_expr.field_indices) {
+            let ptr = builder
+                .build_struct_gep(llvm_type, instance, *index, name)
+                .unwrap();
+            let llvm_value = self.build_expr(module, builder, expr);
+            let value = self.to_basic_value(builder, expr.r#type().as_ref().unwrap(), llvm_value);
+            builder.build_store(ptr, value);
+        }
+
+        instance
+    }
+
+    fn build_func_expr(
+        &'ctx self,
+        module: &Module<'ctx>,
+        builder: &Builder<'ctx>,
+        func_expr: &FuncExpr,
+    ) -> FunctionValue {
+        let llvm_func_type =
+            self.compile_func_type(func_expr.r#type.as_ref().unwrap().cast::<FuncType>());
+        let function = module.add_function("", llvm_func_type, None);
+        self.build_func_body(module, function, func_expr);
+        function
+    }
+
+    fn build_value_ref(&'ctx self, builder: &Builder<'ctx>, value_id: u64) -> AnyValueEnum {
+        let (llvm_type, llvm_value) = self.get_value(value_id);
+        if !self.stored_values.borrow().contains(&value_id)
+            || llvm_type.is_struct_type()
+            || llvm_type.is_array_type()
+        {
+            return llvm_value;
+        }
+        builder
+            .build_load(
+                self.to_basic_type(llvm_type),
+                llvm_value.into_pointer_value(),
+                "",
+            )
+            .as_any_value_enum()
+    }
+
     // ==================================================
 
-    fn compile_type(&self, r#type: &Type) -> AnyTypeEnum {
+    fn compile_type(&'ctx self, r#type: &Type) -> AnyTypeEnum {
         match r#type.kind() {
             TypeKind::Int => self.compile_int_type(r#type.cast::<IntType>()).into(),
+            TypeKind::Bool => self.context.bool_type().into(),
             TypeKind::Func => self.compile_func_type(r#type.cast::<FuncType>()).into(),
             TypeKind::Ptr => self.compile_ptr_type(r#type.cast::<PtrType>()).into(),
             TypeKind::Ref => self.compile_ref_type(r#type.cast::<RefType>()).into(),
-            TypeKind::Array => todo!(),
+            TypeKind::Array => {
+                let array_type = r#type.cast::<ArrayType>();
+                match array_type.len {
+                    Some(_) => self.compile_array_type(array_type).into(),
+                    None => self.compile_slice_type(array_type).into(),
+                }
+            }
             TypeKind::Composite => self
                 .compile_composite_type(r#type.cast::<CompositeType>())
                 .into(),
+            TypeKind::Enum => self.compile_enum_type(r#type.cast::<EnumType>()).into(),
+            TypeKind::Interface => self.compile_interface_type().into(),
+            TypeKind::Tuple => self.compile_tuple_type(r#type.cast::<TupleType>()).into(),
+            TypeKind::Optional => self
+                .compile_optional_type(r#type.cast::<OptionalType>())
+                .as_any_type_enum(),
+            TypeKind::Void | TypeKind::Never => self.context.void_type().into(),
         }
     }
 
-    fn compile_func_type(&self, func_type: &FuncType) -> inkwell::types::FunctionType {
+    fn compile_func_type(&'ctx self, func_type: &FuncType) -> inkwell::types::FunctionType {
         let return_type = self.compile_type(&func_type.return_type);
-        let params = if func_type.is_var_args {
+        // Only extern functions use C varargs, native ones take their extra arguments as a slice
+        let is_var_args = func_type.is_var_args && func_type.is_extern;
+        let params = if is_var_args {
             &func_type.params[..func_type.params.len() - 1]
         } else {
             &func_type.params
@@ -401,33 +2238,33 @@ impl<'ctx> Codegen<'ctx> {
         match return_type {
             AnyTypeEnum::ArrayType(_) => return_type
                 .into_array_type()
-                .fn_type(&param_types, func_type.is_var_args),
+                .fn_type(&param_types, is_var_args),
             AnyTypeEnum::FloatType(_) => return_type
                 .into_float_type()
-                .fn_type(&param_types, func_type.is_var_args),
+                .fn_type(&param_types, is_var_args),
             AnyTypeEnum::FunctionType(_) => return_type
                 .into_function_type()
                 .ptr_type(AddressSpace::default())
-                .fn_type(&param_types, func_type.is_var_args),
+                .fn_type(&param_types, is_var_args),
             AnyTypeEnum::IntType(_) => return_type
                 .into_int_type()
-                .fn_type(&param_types, func_type.is_var_args),
+                .fn_type(&param_types, is_var_args),
             AnyTypeEnum::PointerType(_) => return_type
                 .into_pointer_type()
-                .fn_type(&param_types, func_type.is_var_args),
+                .fn_type(&param_types, is_var_args),
             AnyTypeEnum::StructType(_) => return_type
                 .into_struct_type()
-                .fn_type(&param_types, func_type.is_var_args),
+                .fn_type(&param_types, is_var_args),
             AnyTypeEnum::VectorType(_) => return_type
                 .into_vector_type()
-                .fn_type(&param_types, func_type.is_var_args),
+                .fn_type(&param_types, is_var_args),
             AnyTypeEnum::VoidType(_) => return_type
                 .into_void_type()
-                .fn_type(&param_types, func_type.is_var_args),
+                .fn_type(&param_types, is_var_args),
         }
     }
 
-    fn compile_int_type(&self, int_type: &IntType) -> inkwell::types::IntType {
+    fn compile_int_type(&'ctx self, int_type: &IntType) -> inkwell::types::IntType {
         match int_type {
             IntType::I8 => self.context.i8_type(),
             IntType::I32 => self.context.i32_type(),
@@ -435,7 +2272,7 @@ impl<'ctx> Codegen<'ctx> {
         }
     }
 
-    fn compile_ptr_type(&self, ptr_type: &PtrType) -> inkwell::types::PointerType {
+    fn compile_ptr_type(&'ctx self, ptr_type: &PtrType) -> inkwell::types::PointerType {
         let pointee_type = self.compile_type(&ptr_type.pointee);
         match pointee_type {
             AnyTypeEnum::ArrayType(_) => pointee_type
@@ -463,38 +2300,241 @@ impl<'ctx> Codegen<'ctx> {
         }
     }
 
-    fn compile_ref_type(&self, ref_type: &RefType) -> AnyTypeEnum {
-        self.get_type(
-            ref_type
-                .type_id
-                .expect(&format!("Failed to get ref type: {:?}", ref_type)),
-        )
-        // self.context
-        //     .opaque_struct_type(&ref_type.name)
-        //     .ptr_type(AddressSpace::default())
+    fn compile_ref_type(&'ctx self, ref_type: &RefType) -> AnyTypeEnum {
+        let type_id = ref_type
+            .type_id
+            .expect(&format!("Failed to get ref type: {:?}", ref_type));
+        self.define_pending_type(type_id);
+        self.get_type(type_id)
+    }
+
+    fn define_pending_type(&'ctx self, type_id: u64) -> bool {
+        let Some(r#type) = self.pending_types.borrow_mut().remove(&type_id) else {
+            return false;
+        };
+        self.define_type(type_id, &r#type);
+        true
+    }
+
+    // Composite and enum types are declared as opaque named structs before their body is
+    // set, which lets them refer to themselves through pointers
+    fn declare_type(&'ctx self, type_id: u64, name: &str, r#type: &Type) {
+        if matches!(r#type.kind(), TypeKind::Composite | TypeKind::Enum) {
+            let struct_type = self.context.opaque_struct_type(name);
+            self.set_type(type_id, struct_type.into());
+        }
+    }
+
+    fn define_type(&'ctx self, type_id: u64, r#type: &Type) {
+        let field_types = match r#type.kind() {
+            TypeKind::Composite => self.compile_field_types(r#type.cast::<CompositeType>()),
+            TypeKind::Enum => self.compile_enum_fields(r#type.cast::<EnumType>()),
+            _ => {
+                let llvm_type = self.compile_type(r#type);
+                self.set_type(type_id, llvm_type);
+                return;
+            }
+        };
+        self.get_type(type_id)
+            .into_struct_type()
+            .set_body(&field_types, false);
+    }
+
+    fn compile_composite_type(
+        &'ctx self,
+        composite_type: &CompositeType,
+    ) -> inkwell::types::StructType {
+        let field_types = self.compile_field_types(composite_type);
+        self.context.struct_type(&field_types, false)
     }
 
-    fn compile_composite_type(&self, composite_type: &CompositeType) -> inkwell::types::StructType {
-        let field_types: Vec<BasicTypeEnum> = composite_type
+    fn compile_field_types(&'ctx self, composite_type: &CompositeType) -> Vec<BasicTypeEnum> {
+        composite_type
             .fields
             .iter()
-            .map(|field| {
-                let llvm_type = self.compile_type(&field.r#type);
-                match llvm_type {
-                    AnyTypeEnum::ArrayType(_) => llvm_type.into_array_type().into(),
-                    AnyTypeEnum::FloatType(_) => llvm_type.into_float_type().into(),
-                    AnyTypeEnum::FunctionType(_) => llvm_type
-                        .into_function_type()
-                        .ptr_type(AddressSpace::default())
-                        .into(),
-                    AnyTypeEnum::IntType(_) => llvm_type.into_int_type().into(),
-                    AnyTypeEnum::PointerType(_) => llvm_type.into_pointer_type().into(),
-                    AnyTypeEnum::StructType(_) => llvm_type.into_struct_type().into(),
-                    AnyTypeEnum::VectorType(_) => llvm_type.into_vector_type().into(),
-                    AnyTypeEnum::VoidType(_) => panic!("Cannot have void type in fields"),
-                }
-            })
+            .map(|field| self.to_basic_type(self.compile_type(&field.r#type)))
+            .collect()
+    }
+
+    fn compile_array_type(&'ctx self, array_type: &ArrayType) -> inkwell::types::ArrayType {
+        let len = array_type.len.unwrap();
+        match self.to_basic_type(self.compile_type(&array_type.elem_type)) {
+            BasicTypeEnum::ArrayType(t) => t.array_type(len),
+            BasicTypeEnum::FloatType(t) => t.array_type(len),
+            BasicTypeEnum::IntType(t) => t.array_type(len),
+            BasicTypeEnum::PointerType(t) => t.array_type(len),
+            BasicTypeEnum::StructType(t) => t.array_type(len),
+            BasicTypeEnum::VectorType(t) => t.array_type(len),
+        }
+    }
+
+    fn compile_slice_type(&'ctx self, array_type: &ArrayType) -> inkwell::types::StructType {
+        let ptr_type = self.compile_ptr_type(&PtrType::new(array_type.elem_type.clone()));
+        self.context
+            .struct_type(&[ptr_type.into(), self.context.i64_type().into()], false)
+    }
+
+    fn compile_tuple_type(&'ctx self, tuple_type: &TupleType) -> inkwell::types::StructType {
+        let elem_types: Vec<BasicTypeEnum> = tuple_type
+            .elem_types
+            .iter()
+            .map(|elem_type| self.to_basic_type(self.compile_type(elem_type)))
             .collect();
+        self.context.struct_type(&elem_types, false)
+    }
+
+    // Interface values are a (data pointer, vtable pointer) pair, the vtable holding one
+    // function pointer per method in declaration order
+    fn compile_interface_type(&'ctx self) -> inkwell::types::StructType {
+        let i8_ptr_type = self.context.i8_type().ptr_type(AddressSpace::default());
+        self.context.struct_type(
+            &[
+                i8_ptr_type.into(),
+                i8_ptr_type.ptr_type(AddressSpace::default()).into(),
+            ],
+            false,
+        )
+    }
+
+    // Optional pointers are nullable pointers, other optional values are pairs of a
+    // presence flag and the value
+    fn compile_optional_type(&'ctx self, optional_type: &OptionalType) -> BasicTypeEnum {
+        let inner_type = self.to_basic_type(self.compile_type(&optional_type.inner));
+        if inner_type.is_pointer_type() {
+            return inner_type;
+        }
+        self.context
+            .struct_type(&[self.context.bool_type().into(), inner_type], false)
+            .into()
+    }
+
+    fn compile_enum_type(&'ctx self, enum_type: &EnumType) -> inkwell::types::StructType {
+        let field_types = self.compile_enum_fields(enum_type);
         self.context.struct_type(&field_types, false)
     }
+
+    fn compile_enum_fields(&'ctx self, enum_type: &EnumType) -> Vec<BasicTypeEnum> {
+        let target_data = self.target_machine.get_target_data();
+        let (mut size, mut align) = (0, 1);
+        for payload in enum_type.variants.iter().filter_map(|v| v.payload.as_ref()) {
+            let llvm_type = self.to_basic_type(self.compile_type(payload));
+            size = size.max(target_data.get_abi_size(&llvm_type));
+            align = align.max(target_data.get_abi_alignment(&llvm_type));
+        }
+
+        let tag_type = self.context.i32_type().into();
+        if size == 0 {
+            return vec![tag_type];
+        }
+        let payload_type = self
+            .context
+            .custom_width_int_type(align * 8)
+            .array_type(size.div_ceil(align.into()) as u32);
+        vec![tag_type, payload_type.into()]
+    }
+
+    // ==================================================
+
+    fn owner_type<'a>(&self, expr: &'a Expr) -> &'a Type {
+        let r#type = expr.r#type().as_ref().unwrap();
+        match r#type.kind() {
+            TypeKind::Ptr => &r#type.cast::<PtrType>().pointee,
+            _ => r#type,
+        }
+    }
+
+    fn build_path_gep(
+        &'ctx self,
+        builder: &Builder<'ctx>,
+        owner_type: &Type,
+        ptr: PointerValue<'ctx>,
+        path: &[u32],
+        name: &str,
+    ) -> PointerValue<'ctx> {
+        if path.is_empty() {
+            return ptr;
+        }
+        let i32_type = self.context.i32_type();
+        let mut indices = vec![i32_type.const_zero()];
+        indices.extend(path.iter().map(|&i| i32_type.const_int(i.into(), false)));
+        let llvm_type = self.to_basic_type(self.compile_type(owner_type));
+        unsafe { builder.build_in_bounds_gep(llvm_type, ptr, &indices, name) }
+    }
+
+    fn is_aggregate(&'ctx self, r#type: &Type) -> bool {
+        let llvm_type = self.compile_type(r#type);
+        llvm_type.is_struct_type() || llvm_type.is_array_type()
+    }
+
+    // Aggregates flow through branches as pointers to their storage
+    fn to_incoming_value(
+        &'ctx self,
+        builder: &Builder<'ctx>,
+        r#type: &Type,
+        llvm_value: AnyValueEnum<'ctx>,
+    ) -> BasicValueEnum<'ctx> {
+        match llvm_value {
+            AnyValueEnum::PointerValue(ptr) if self.is_aggregate(r#type) => ptr.into(),
+            _ => self.to_basic_value(builder, r#type, llvm_value),
+        }
+    }
+
+    fn to_basic_type<'a>(&self, llvm_type: AnyTypeEnum<'a>) -> BasicTypeEnum<'a> {
+        match llvm_type {
+            AnyTypeEnum::ArrayType(_) => llvm_type.into_array_type().into(),
+            AnyTypeEnum::FloatType(_) => llvm_type.into_float_type().into(),
+            AnyTypeEnum::FunctionType(_) => llvm_type
+                .into_function_type()
+                .ptr_type(AddressSpace::default())
+                .into(),
+            AnyTypeEnum::IntType(_) => llvm_type.into_int_type().into(),
+            AnyTypeEnum::PointerType(_) => llvm_type.into_pointer_type().into(),
+            AnyTypeEnum::StructType(_) => llvm_type.into_struct_type().into(),
+            AnyTypeEnum::VectorType(_) => llvm_type.into_vector_type().into(),
+            AnyTypeEnum::VoidType(_) => panic!("Cannot have a value of void type"),
+        }
+    }
+
+    fn to_basic_value(
+        &'ctx self,
+        builder: &Builder<'ctx>,
+        r#type: &Type,
+        llvm_value: AnyValueEnum<'ctx>,
+    ) -> BasicValueEnum<'ctx> {
+        match llvm_value {
+            AnyValueEnum::FunctionValue(function) => {
+                function.as_global_value().as_pointer_value().into()
+            }
+            AnyValueEnum::PointerValue(ptr) if self.is_aggregate(r#type) => {
+                let llvm_type = self.to_basic_type(self.compile_type(r#type));
+                builder.build_load(llvm_type, ptr, "")
+            }
+            _ => llvm_value.try_into().unwrap(),
+        }
+    }
+}
+
+fn null_test_operand(binary_expr: &BinaryExpr) -> Option<&Expr> {
+    match (binary_expr.lhs.kind(), binary_expr.rhs.kind()) {
+        (ExprKind::NullLiteral, _) => Some(&binary_expr.rhs),
+        (_, ExprKind::NullLiteral) => Some(&binary_expr.lhs),
+        _ => None,
+    }
+}
+
+fn is_switchable(pattern: &Pattern) -> bool {
+    match pattern {
+        Pattern::Wildcard | Pattern::Binding(_) => true,
+        Pattern::IntLiteral(_) | Pattern::BoolLiteral(_) => true,
+        Pattern::Variant(variant_pattern) => variant_pattern
+            .payload
+            .as_deref()
+            .is_none_or(|payload| matches!(payload, Pattern::Wildcard | Pattern::Binding(_))),
+        Pattern::Tuple(_) => false,
+        Pattern::Or(patterns) => patterns.iter().all(|pattern| {
+            !matches!(pattern, Pattern::Or(_))
+                && is_switchable(pattern)
+                && pattern.value_ids().is_empty()
+        }),
+    }
 }
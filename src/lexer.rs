@@ -5,7 +5,7 @@ use crate::char_reader::{CharReader, EOL};
 use crate::token::{CursorPos, Token, TokenKind};
 
 const PUNCTUATIONS: phf::Set<char> = phf_set! {
-    '=', ',', '.', '!', '(', ')', '[', ']', '{', '}', ':', ';', '+', '-', '<', '>', '*', '@', '"', '\''
+    '=', ',', '.', '!', '(', ')', '[', ']', '{', '}', ':', ';', '+', '-', '<', '>', '*', '/', '?', '@', '|', '"', '\''
 };
 
 fn is_punctuation(c: char) -> bool {
@@ -114,7 +114,7 @@ impl<'a> Lexer<'a> {
                         self.skip_char();
                         self.skip_char();
                     }
-                    _ => (),
+                    _ => break,
                 },
                 _ => break,
             }
@@ -152,7 +152,13 @@ impl<'a> Lexer<'a> {
 
                 if matches!(
                     self.last_token_kind,
-                    Some(TokenKind::Identifier | TokenKind::RightParen | TokenKind::StrLiteral)
+                    Some(
+                        TokenKind::Identifier
+                            | TokenKind::RightParen
+                            | TokenKind::RightBracket
+                            | TokenKind::IntLiteral
+                            | TokenKind::StrLiteral
+                    )
                 ) {
                     TokenKind::Dot
                 } else {
@@ -164,6 +170,9 @@ impl<'a> Lexer<'a> {
                 if self.curr_char == Some('=') {
                     self.accept_char();
                     TokenKind::Equal
+                } else if self.curr_char == Some('>') {
+                    self.accept_char();
+                    TokenKind::FatArrow
                 } else {
                     TokenKind::Assign
                 }
@@ -194,11 +203,21 @@ impl<'a> Lexer<'a> {
             }
             Some('<') => {
                 self.accept_char();
-                TokenKind::LeftChevron
+                if self.curr_char == Some('=') {
+                    self.accept_char();
+                    TokenKind::LessEqual
+                } else {
+                    TokenKind::LeftChevron
+                }
             }
             Some('>') => {
                 self.accept_char();
-                TokenKind::RightChevron
+                if self.curr_char == Some('=') {
+                    self.accept_char();
+                    TokenKind::GreaterEqual
+                } else {
+                    TokenKind::RightChevron
+                }
             }
             Some('+') => {
                 self.accept_char();
@@ -237,6 +256,19 @@ impl<'a> Lexer<'a> {
                     TokenKind::Not
                 }
             }
+            Some('|') => {
+                self.accept_char();
+                TokenKind::Pipe
+            }
+            Some('?') => {
+                self.accept_char();
+                if self.curr_char == Some('?') {
+                    self.accept_char();
+                    TokenKind::Coalesce
+                } else {
+                    TokenKind::Question
+                }
+            }
             Some('"') => {
                 self.accept_char();
                 loop {
@@ -292,6 +324,11 @@ impl<'a> Lexer<'a> {
                     }
                 }
 
+                // Tuple indices such as `pair.0.1` never have a fraction part
+                if self.last_token_kind == Some(TokenKind::Dot) {
+                    return TokenKind::IntLiteral;
+                }
+
                 match self.curr_char {
                     Some('.') => {
                         if self.inspect_char(0) != Some('.') {
@@ -331,18 +368,32 @@ impl<'a> Lexer<'a> {
 
     fn extract_keyword(&self) -> Option<TokenKind> {
         match self.spelling.as_str() {
+            "const" => Some(TokenKind::Const),
+            "else" => Some(TokenKind::Else),
+            "enum" => Some(TokenKind::Enum),
+            "extend" => Some(TokenKind::Extend),
             "extern" => Some(TokenKind::Extern),
             "export" => Some(TokenKind::Export),
+            "if" => Some(TokenKind::If),
+            "implements" => Some(TokenKind::Implements),
             "import" => Some(TokenKind::Import),
+            "inherit" => Some(TokenKind::Inherit),
+            "interface" => Some(TokenKind::Interface),
             "let" => Some(TokenKind::Let),
+            "match" => Some(TokenKind::Match),
             "mut" => Some(TokenKind::Mut),
+            "null" => Some(TokenKind::Null),
+            "pub" => Some(TokenKind::Pub),
             "restrict" => Some(TokenKind::Restrict),
             "return" => Some(TokenKind::Return),
             "type" => Some(TokenKind::Type),
 
+            "bool" => Some(TokenKind::Bool),
             "i8" => Some(TokenKind::I8),
             "i32" => Some(TokenKind::I32),
             "i64" => Some(TokenKind::I64),
+            "never" => Some(TokenKind::Never),
+            "void" => Some(TokenKind::Void),
             _ => None,
         }
     }
@@ -15,6 +15,7 @@ mod char_reader;
 mod codegen;
 mod lexer;
 mod parser;
+mod prelude;
 mod symbol_table;
 mod token;
 mod utils;
@@ -33,6 +34,14 @@ struct Args {
 
     #[arg(short, long)]
     output: Option<String>,
+
+    /// Omit runtime checks such as array bounds checking
+    #[arg(long)]
+    release: bool,
+
+    /// Warn when a local declaration shadows another value
+    #[arg(long)]
+    warn_shadowing: bool,
 }
 
 fn main() {
@@ -56,7 +65,7 @@ fn main() {
     let mut parser = Parser::new(&args.source, &file);
     let mut module_ast = parser.parse();
 
-    let mut checker = Checker::new();
+    let mut checker = Checker::new(args.warn_shadowing);
     checker.check(&mut module_ast);
 
     if args.ast {
@@ -64,6 +73,6 @@ fn main() {
         return;
     }
 
-    let codegen = Codegen::new();
+    let codegen = Codegen::new(!args.release);
     codegen.build_module(&module_ast, args.output.as_deref());
 }
@@ -1,37 +1,66 @@
-use std::{fs::File, mem};
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    fs::{self, File},
+    mem,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
 use crate::{
     ast::{
         arg::Arg,
         decl::Decl,
         expr::{
-            CallExpr, CompositeExpr, Expr, IdentExpr, IntLiteralExpr, MemberExpr, StrLiteralExpr,
+            ArrayExpr, ArrayRepeatExpr, BinaryExpr, BinaryOp, BoolLiteralExpr, CallExpr,
+            CompositeExpr, Expr, ExprKind, FuncExpr, IdentExpr, IfExpr, IndexExpr, IntLiteralExpr,
+            MatchArm, MatchExpr, MemberExpr, NullLiteralExpr, SliceExpr, StrLiteralExpr, TryExpr,
+            TupleExpr,
         },
+        field::Field,
         ident::Ident,
         module_ast::ModuleAST,
         param::Param,
-        stmt::{DeclStmt, ExprStmt, ExternStmt, ReturnStmt, Stmt, TypeStmt},
-        types::{ArrayType, CompositeType, FuncType, IntType, PtrType, RefType, Type},
+        pattern::{Pattern, TuplePattern, VariantPattern},
+        stmt::{
+            AssignStmt, DeclStmt, ExprStmt, ExtendStmt, ExternStmt, ReturnStmt, Stmt, StmtKind,
+            TypeStmt,
+        },
+        type_param::TypeParam,
+        types::{
+            ArrayType, BoolType, CompositeType, EnumType, FuncType, IntType, InterfaceType,
+            NeverType, OptionalType, PtrType, RefType, TupleType, Type, TypeKind, VoidType,
+        },
+        variant::Variant,
     },
     lexer::Lexer,
-    token::{Token, TokenKind},
+    token::{CursorPos, Token, TokenKind},
 };
 
 pub struct Parser<'a> {
     lexer: Lexer<'a>,
     curr_token: Token,
+    next_token: Token,
+    last_end: CursorPos,
     module_name: String,
+    // Files parsed so far by this parser and those it started for imports
+    parsed_files: Rc<RefCell<HashSet<PathBuf>>>,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(module_name: &str, input: &'a File) -> Self {
         let mut lexer = Lexer::new(input);
         let curr_token = lexer.next_token();
+        let next_token = lexer.next_token();
 
+        let parsed_files = HashSet::from_iter(fs::canonicalize(module_name));
         Self {
             lexer,
             curr_token,
+            next_token,
+            last_end: (1, 0),
             module_name: module_name.to_string(),
+            parsed_files: Rc::new(RefCell::new(parsed_files)),
         }
     }
 
@@ -42,7 +71,16 @@ impl<'a> Parser<'a> {
     // ==================================================
 
     fn accept_token(&mut self) -> Token {
-        mem::replace(&mut self.curr_token, self.lexer.next_token())
+        let next_token = mem::replace(&mut self.next_token, self.lexer.next_token());
+        let token = mem::replace(&mut self.curr_token, next_token);
+        self.last_end = token.end();
+        token
+    }
+
+    // Calls and indexing must start on the line of their operand, otherwise a
+    // parenthesized or array expression on the next line would be swallowed
+    fn is_continuation(&self) -> bool {
+        self.curr_token.begin().0 == self.last_end.0
     }
 
     fn expect_token(&mut self, expected_kind: TokenKind) -> Token {
@@ -61,24 +99,76 @@ impl<'a> Parser<'a> {
 
     fn parse_module(&mut self) -> ModuleAST {
         let mut stmts: Vec<Stmt> = Vec::new();
+        let mut imports = Vec::new();
 
         while !self.curr_token.is_kind(TokenKind::EOF) {
+            if self.curr_token.is_kind(TokenKind::Import) {
+                imports.push(self.parse_import());
+                continue;
+            }
             stmts.push(self.parse_stmt());
         }
 
-        ModuleAST::new(self.module_name.clone(), stmts)
+        let mut module = ModuleAST::new(self.module_name.clone(), stmts);
+        module.imports = imports;
+        module
+    }
+
+    // `import "shapes.sta"` parses another file as a module of its own, found relative to
+    // the importing file. A file is parsed once, so importing it again, including through
+    // a cycle, yields an empty module that only names it
+    fn parse_import(&mut self) -> ModuleAST {
+        self.expect_token(TokenKind::Import);
+        let token = self.expect_token(TokenKind::StrLiteral);
+        let spelling = token.spelling();
+        let path = Path::new(&self.module_name)
+            .with_file_name(&spelling[1..spelling.len() - 1])
+            .to_string_lossy()
+            .into_owned();
+        let file = File::open(&path)
+            .unwrap_or_else(|_| panic!("Cannot open module {:?}: {:?}", path, token.begin()));
+        let canonical_path = fs::canonicalize(&path).unwrap();
+        if !self.parsed_files.borrow_mut().insert(canonical_path) {
+            return ModuleAST::new(path, Vec::new());
+        }
+        let mut parser = Parser::new(&path, &file);
+        parser.parsed_files = self.parsed_files.clone();
+        parser.parse()
     }
 
     // ==================================================
 
     fn parse_stmt(&mut self) -> Stmt {
         match self.curr_token.kind() {
-            TokenKind::Let | TokenKind::Export => self.parse_decl_stmt().into(),
+            TokenKind::Let | TokenKind::Const | TokenKind::Export => self.parse_decl_stmt().into(),
+            TokenKind::Extend => self.parse_extend_stmt().into(),
             TokenKind::Extern => self.parse_extern_stmt().into(),
             TokenKind::Return => self.parse_return_stmt().into(),
             TokenKind::Type => self.parse_type_stmt().into(),
-            _ => ExprStmt::new(self.parse_expr()).into(),
+            _ => {
+                let expr = self.parse_expr();
+                if self.curr_token.is_kind(TokenKind::Assign) {
+                    self.accept_token();
+                    AssignStmt::new(expr, self.parse_expr()).into()
+                } else {
+                    ExprStmt::new(expr).into()
+                }
+            }
+        }
+    }
+
+    fn parse_extend_stmt(&mut self) -> ExtendStmt {
+        self.expect_token(TokenKind::Extend);
+        let ident = self.parse_ident();
+        self.expect_token(TokenKind::LeftBrace);
+
+        let mut statics = Vec::new();
+        while !self.curr_token.is_kind(TokenKind::RightBrace) {
+            statics.push(self.parse_static());
         }
+
+        self.accept_token();
+        ExtendStmt::new(ident, statics)
     }
 
     fn parse_extern_stmt(&mut self) -> ExternStmt {
@@ -101,7 +191,13 @@ impl<'a> Parser<'a> {
             self.accept_token();
         }
 
-        self.expect_token(TokenKind::Let);
+        let is_const = if self.curr_token.is_kind(TokenKind::Const) {
+            self.accept_token();
+            true
+        } else {
+            self.expect_token(TokenKind::Let);
+            false
+        };
 
         let mut decls: Vec<Decl> = Vec::new();
         decls.push(self.parse_decl());
@@ -110,12 +206,18 @@ impl<'a> Parser<'a> {
             self.accept_token();
             decls.push(self.parse_decl());
         }
+        for decl in &mut decls {
+            decl.is_const = is_const;
+        }
 
         DeclStmt::new(decls, is_export)
     }
 
     fn parse_return_stmt(&mut self) -> ReturnStmt {
         self.expect_token(TokenKind::Return);
+        if self.curr_token.is_kind(TokenKind::RightBrace) {
+            return ReturnStmt::new(None);
+        }
         let expr = self.parse_expr();
         ReturnStmt::new(Some(expr))
     }
@@ -123,16 +225,35 @@ impl<'a> Parser<'a> {
     fn parse_type_stmt(&mut self) -> TypeStmt {
         self.expect_token(TokenKind::Type);
 
+        let pos = self.curr_token.begin();
         let ident = self.parse_ident();
+        let type_params = self.parse_type_params();
         let r#type = self.parse_type();
 
-        return TypeStmt::new(ident, r#type);
+        let mut type_stmt = TypeStmt::new(ident, r#type);
+        type_stmt.type_params = type_params;
+        type_stmt.pos = Some(pos);
+        return type_stmt;
     }
 
     // ==================================================
 
     fn parse_decl(&mut self) -> Decl {
-        let ident = self.parse_ident();
+        let is_mut = if self.curr_token.is_kind(TokenKind::Mut) {
+            self.accept_token();
+            true
+        } else {
+            false
+        };
+
+        let pos = self.curr_token.begin();
+        let mut pattern = None;
+        let name = if self.curr_token.is_kind(TokenKind::LeftParen) {
+            pattern = Some(self.parse_pattern());
+            String::new()
+        } else {
+            self.parse_ident().name
+        };
         let mut r#type = None;
 
         if self.curr_token.is_kind(TokenKind::Colon) {
@@ -140,24 +261,74 @@ impl<'a> Parser<'a> {
             r#type = Some(self.parse_type());
         }
 
-        if self.curr_token.is_kind(TokenKind::Assign) {
+        let mut decl = if self.curr_token.is_kind(TokenKind::Assign) {
             self.accept_token();
             let value = self.parse_expr();
-            Decl::new(ident.name, r#type, Some(value))
+            Decl::new(name, r#type, Some(value), is_mut)
         } else {
-            Decl::new(ident.name, r#type, None)
-        }
+            Decl::new(name, r#type, None, is_mut)
+        };
+        decl.pattern = pattern;
+        decl.pos = Some(pos);
+        decl
     }
 
     // ==================================================
 
     fn parse_expr(&mut self) -> Expr {
+        self.parse_binary_expr(1)
+    }
+
+    // Operators of at least `min_precedence` bind tighter than the caller's operator
+    fn parse_binary_expr(&mut self, min_precedence: u8) -> Expr {
+        let mut lhs = self.parse_postfix_expr();
+        while let Some(op) = self.binary_op() {
+            if op.precedence() < min_precedence {
+                break;
+            }
+            self.accept_token();
+            let rhs = match op.is_right_assoc() {
+                true => self.parse_binary_expr(op.precedence()),
+                false => self.parse_binary_expr(op.precedence() + 1),
+            };
+            lhs = BinaryExpr::new(op, lhs, rhs).into();
+        }
+        lhs
+    }
+
+    fn binary_op(&self) -> Option<BinaryOp> {
+        match self.curr_token.kind() {
+            TokenKind::Plus => Some(BinaryOp::Add),
+            TokenKind::Minus => Some(BinaryOp::Sub),
+            TokenKind::Multiply => Some(BinaryOp::Mul),
+            TokenKind::Divide => Some(BinaryOp::Div),
+            TokenKind::Equal => Some(BinaryOp::Eq),
+            TokenKind::NotEqual => Some(BinaryOp::Ne),
+            TokenKind::LeftChevron => Some(BinaryOp::Lt),
+            TokenKind::RightChevron => Some(BinaryOp::Gt),
+            TokenKind::LessEqual => Some(BinaryOp::Le),
+            TokenKind::GreaterEqual => Some(BinaryOp::Ge),
+            TokenKind::Coalesce => Some(BinaryOp::Coalesce),
+            _ => None,
+        }
+    }
+
+    fn parse_postfix_expr(&mut self) -> Expr {
         let mut expr = self.parse_primary_expr();
 
         loop {
             match self.curr_token.kind() {
-                TokenKind::LeftParen => expr = self.parse_call_expr(expr).into(),
+                TokenKind::LeftParen if self.is_continuation() => {
+                    expr = self.parse_call_expr(expr).into()
+                }
                 TokenKind::Dot => expr = self.parse_member_expr(expr).into(),
+                TokenKind::LeftBracket if self.is_continuation() => {
+                    expr = self.parse_index_expr(expr)
+                }
+                TokenKind::Question => {
+                    self.accept_token();
+                    expr = TryExpr::new(expr).into()
+                }
                 _ => break,
             }
         }
@@ -177,7 +348,26 @@ impl<'a> Parser<'a> {
                 let spelling = token.spelling();
                 StrLiteralExpr::new(spelling[1..spelling.len() - 1].to_string()).into()
             }
-            TokenKind::LeftBrace => self.parse_composite_expr().into(),
+            TokenKind::BoolLiteral => {
+                let token = self.accept_token();
+                BoolLiteralExpr::new(token.spelling() == "true").into()
+            }
+            TokenKind::Null => {
+                self.accept_token();
+                NullLiteralExpr::new().into()
+            }
+            TokenKind::Match => self.parse_match_expr().into(),
+            TokenKind::If => self.parse_if_expr().into(),
+            TokenKind::LeftBracket => self.parse_array_expr(),
+            TokenKind::LeftParen => self.parse_paren_expr(),
+            TokenKind::LeftBrace => {
+                self.accept_token();
+                if self.curr_token.is_kind(TokenKind::LeftBrace) {
+                    self.parse_func_expr().into()
+                } else {
+                    self.parse_composite_expr().into()
+                }
+            }
             _ => panic!(
                 "unexpected expression at {:?}: {:?}",
                 self.curr_token.begin(),
@@ -186,38 +376,235 @@ impl<'a> Parser<'a> {
         }
     }
 
+    fn parse_paren_expr(&mut self) -> Expr {
+        self.expect_token(TokenKind::LeftParen);
+        let expr = self.parse_expr();
+        if !self.curr_token.is_kind(TokenKind::Comma) {
+            self.expect_token(TokenKind::RightParen);
+            return expr;
+        }
+
+        let mut elems = vec![expr];
+        while self.curr_token.is_kind(TokenKind::Comma) {
+            self.accept_token();
+            if self.curr_token.is_kind(TokenKind::RightParen) {
+                break;
+            }
+            elems.push(self.parse_expr());
+        }
+        self.expect_token(TokenKind::RightParen);
+        TupleExpr::new(elems).into()
+    }
+
     fn parse_call_expr(&mut self, postfix_expr: Expr) -> CallExpr {
         let arg_list = self.parse_arg_list();
         CallExpr::new(postfix_expr, arg_list)
     }
 
+    fn parse_index_expr(&mut self, postfix_expr: Expr) -> Expr {
+        self.expect_token(TokenKind::LeftBracket);
+        let lo = if self.curr_token.is_kind(TokenKind::To) {
+            None
+        } else {
+            Some(self.parse_expr())
+        };
+
+        if self.curr_token.is_kind(TokenKind::To) {
+            self.accept_token();
+            let hi = if self.curr_token.is_kind(TokenKind::RightBracket) {
+                None
+            } else {
+                Some(self.parse_expr())
+            };
+            self.expect_token(TokenKind::RightBracket);
+            return SliceExpr::new(postfix_expr, lo, hi).into();
+        }
+
+        self.expect_token(TokenKind::RightBracket);
+        IndexExpr::new(postfix_expr, lo.unwrap()).into()
+    }
+
     fn parse_member_expr(&mut self, postfix_expr: Expr) -> MemberExpr {
         self.expect_token(TokenKind::Dot);
-        let member = self.parse_ident();
+        let member = if self.curr_token.is_kind(TokenKind::IntLiteral) {
+            Ident::new(self.accept_token().spelling().to_owned())
+        } else {
+            self.parse_ident()
+        };
         MemberExpr::new(postfix_expr, member)
     }
 
-    fn parse_composite_expr(&mut self) -> CompositeExpr {
-        let mut fields = Vec::new();
+    fn parse_array_expr(&mut self) -> Expr {
+        self.expect_token(TokenKind::LeftBracket);
+
+        let mut elems = Vec::new();
+        if !self.curr_token.is_kind(TokenKind::RightBracket) {
+            elems.push(self.parse_expr());
+            if self.curr_token.is_kind(TokenKind::Semicolon) {
+                self.accept_token();
+                let len = self.parse_array_len();
+                self.expect_token(TokenKind::RightBracket);
+                return ArrayRepeatExpr::new(elems.pop().unwrap(), len).into();
+            }
+        }
+        while self.curr_token.is_kind(TokenKind::Comma) {
+            self.accept_token();
+            if self.curr_token.is_kind(TokenKind::RightBracket) {
+                break;
+            }
+            elems.push(self.parse_expr());
+        }
+
+        self.expect_token(TokenKind::RightBracket);
+        ArrayExpr::new(elems).into()
+    }
+
+    fn parse_match_expr(&mut self) -> MatchExpr {
+        self.expect_token(TokenKind::Match);
+        let expr = self.parse_expr();
         self.expect_token(TokenKind::LeftBrace);
 
+        let mut arms = Vec::new();
         loop {
+            if self.curr_token.is_kind(TokenKind::Comma) {
+                self.accept_token();
+            }
             if self.curr_token.is_kind(TokenKind::RightBrace) {
                 break;
             }
+            let pattern = self.parse_pattern();
+            let guard = if self.curr_token.is_kind(TokenKind::If) {
+                self.accept_token();
+                Some(self.parse_expr())
+            } else {
+                None
+            };
+            self.expect_token(TokenKind::FatArrow);
+            let body = self.parse_expr();
+            arms.push(MatchArm::new(pattern, guard, body));
+        }
+
+        self.expect_token(TokenKind::RightBrace);
+        MatchExpr::new(expr, arms)
+    }
+
+    fn parse_composite_expr(&mut self) -> CompositeExpr {
+        let mut fields = Vec::new();
+
+        loop {
             if self.curr_token.is_kind(TokenKind::Comma) {
                 self.accept_token();
             }
-            let name = self.parse_ident().name;
-            self.expect_token(TokenKind::Colon);
-            let value = self.parse_expr();
-            fields.push((name, value));
+            if self.curr_token.is_kind(TokenKind::RightBrace) {
+                break;
+            }
+            let ident = self.parse_ident();
+            let value = if self.curr_token.is_kind(TokenKind::Colon) {
+                self.accept_token();
+                self.parse_expr()
+            } else {
+                IdentExpr::new(ident.clone()).into()
+            };
+            fields.push((ident.name, value));
         }
 
         self.expect_token(TokenKind::RightBrace);
         CompositeExpr::new(fields)
     }
 
+    fn parse_if_expr(&mut self) -> IfExpr {
+        self.expect_token(TokenKind::If);
+        let cond = self.parse_expr();
+        let then_body = self.parse_block();
+
+        let mut else_body = None;
+        if self.curr_token.is_kind(TokenKind::Else) {
+            self.accept_token();
+            else_body = Some(if self.curr_token.is_kind(TokenKind::If) {
+                vec![ExprStmt::new(self.parse_if_expr().into()).into()]
+            } else {
+                self.parse_block()
+            });
+        }
+        IfExpr::new(cond, then_body, else_body)
+    }
+
+    fn parse_block(&mut self) -> Vec<Stmt> {
+        self.expect_token(TokenKind::LeftBrace);
+        let mut stmts = Vec::new();
+        while !self.curr_token.is_kind(TokenKind::RightBrace) {
+            stmts.push(self.parse_stmt());
+        }
+        self.expect_token(TokenKind::RightBrace);
+        stmts
+    }
+
+    fn parse_func_expr(&mut self) -> FuncExpr {
+        self.expect_token(TokenKind::LeftBrace);
+
+        let type_params = self.parse_type_params();
+        let mut is_method = false;
+        if self.curr_token.is_kind(TokenKind::Identifier) && self.curr_token.spelling() == "self" {
+            self.accept_token();
+            is_method = true;
+            if self.curr_token.is_kind(TokenKind::Comma) {
+                self.accept_token();
+            }
+        }
+
+        let mut params = Vec::new();
+        let mut is_var_args = false;
+        while self.curr_token.is_kind(TokenKind::Identifier) {
+            params.push(self.parse_param());
+            if self.curr_token.is_kind(TokenKind::Comma) {
+                self.accept_token();
+            }
+        }
+        if self.curr_token.is_kind(TokenKind::Ellipsis) {
+            self.accept_token();
+            params.push(self.parse_param());
+            is_var_args = true;
+        }
+
+        let mut return_type = None;
+        if self.curr_token.is_kind(TokenKind::Arrow) {
+            self.accept_token();
+            return_type = Some(self.parse_type());
+        }
+        self.expect_token(TokenKind::Semicolon);
+
+        let mut body = Vec::new();
+        while !self.curr_token.is_kind(TokenKind::RightBrace) {
+            body.push(self.parse_stmt());
+        }
+        self.expect_token(TokenKind::RightBrace);
+        self.expect_token(TokenKind::RightBrace);
+
+        // The last expression is returned if the function returns a value. An if without
+        // an else has none, so the function can reach its end after it
+        let returns_value = return_type
+            .as_ref()
+            .is_some_and(|r#type| !matches!(r#type.kind(), TypeKind::Void | TypeKind::Never));
+        let has_value = body.last().is_some_and(|stmt| match stmt.kind() {
+            StmtKind::Expr => {
+                let expr = &stmt.cast::<ExprStmt>().expr;
+                !matches!(expr.kind(), ExprKind::If) || expr.cast::<IfExpr>().else_body.is_some()
+            }
+            _ => false,
+        });
+        if returns_value && has_value {
+            let expr = body.pop().unwrap().cast::<ExprStmt>().expr.clone();
+            let mut return_stmt = ReturnStmt::new(Some(expr));
+            return_stmt.is_implicit = true;
+            body.push(return_stmt.into());
+        }
+
+        let mut func_expr = FuncExpr::new(params, return_type, is_method, body);
+        func_expr.type_params = type_params;
+        func_expr.is_var_args = is_var_args;
+        func_expr
+    }
+
     fn parse_ident_expr(&mut self) -> IdentExpr {
         let ident = self.parse_ident();
         IdentExpr::new(ident)
@@ -230,9 +617,27 @@ impl<'a> Parser<'a> {
         Ident::new(token.spelling().to_owned())
     }
 
+    fn parse_array_len(&mut self) -> u32 {
+        let token = self.expect_token(TokenKind::IntLiteral);
+        token
+            .spelling()
+            .parse()
+            .unwrap_or_else(|_| panic!("Invalid array length: {}", token.spelling()))
+    }
+
     // ==================================================
 
     fn parse_type(&mut self) -> Type {
+        let r#type = self.parse_non_optional_type();
+        // The suffix applies to the whole type, so `*T?` is a nullable pointer
+        if self.curr_token.is_kind(TokenKind::Question) {
+            self.accept_token();
+            return OptionalType::new(r#type).into();
+        }
+        r#type
+    }
+
+    fn parse_non_optional_type(&mut self) -> Type {
         let mut r#type = match self.curr_token.kind() {
             TokenKind::I8 => {
                 self.accept_token();
@@ -246,10 +651,26 @@ impl<'a> Parser<'a> {
                 self.accept_token();
                 IntType::I64.into()
             }
+            TokenKind::Bool => {
+                self.accept_token();
+                BoolType.into()
+            }
+            TokenKind::Void => {
+                self.accept_token();
+                VoidType.into()
+            }
+            TokenKind::Never => {
+                self.accept_token();
+                NeverType.into()
+            }
             TokenKind::Multiply => self.parse_ptr_type().into(),
-            TokenKind::LeftParen => self.parse_func_type().into(),
+            TokenKind::LeftParen => self.parse_paren_type(),
             TokenKind::Identifier => self.parse_ref_type().into(),
-            TokenKind::Restrict | TokenKind::LeftBrace => self.parse_composite_type().into(),
+            TokenKind::Restrict | TokenKind::Implements | TokenKind::LeftBrace => {
+                self.parse_composite_type().into()
+            }
+            TokenKind::Enum => self.parse_enum_type().into(),
+            TokenKind::Interface => self.parse_interface_type().into(),
             _ => panic!(
                 "Unexpected token when parsing type: {}",
                 self.curr_token.spelling()
@@ -258,8 +679,13 @@ impl<'a> Parser<'a> {
 
         while self.curr_token.is_kind(TokenKind::LeftBracket) {
             self.accept_token();
+            let len = if self.curr_token.is_kind(TokenKind::IntLiteral) {
+                Some(self.parse_array_len())
+            } else {
+                None
+            };
             self.expect_token(TokenKind::RightBracket);
-            r#type = ArrayType::new(r#type).into()
+            r#type = ArrayType::new(r#type, len).into()
         }
 
         r#type
@@ -267,10 +693,39 @@ impl<'a> Parser<'a> {
 
     fn parse_ptr_type(&mut self) -> PtrType {
         self.expect_token(TokenKind::Multiply);
-        let pointee = self.parse_type();
+        let pointee = self.parse_non_optional_type();
         PtrType::new(pointee)
     }
 
+    fn parse_paren_type(&mut self) -> Type {
+        self.expect_token(TokenKind::LeftParen);
+        let is_func_type = match self.curr_token.kind() {
+            TokenKind::RightParen | TokenKind::Ellipsis => true,
+            TokenKind::Identifier => self.next_token.is_kind(TokenKind::Colon),
+            _ => false,
+        };
+        if is_func_type {
+            return self.parse_func_type().into();
+        }
+
+        let r#type = self.parse_type();
+        if !self.curr_token.is_kind(TokenKind::Comma) {
+            self.expect_token(TokenKind::RightParen);
+            return r#type;
+        }
+
+        let mut elem_types = vec![r#type];
+        while self.curr_token.is_kind(TokenKind::Comma) {
+            self.accept_token();
+            if self.curr_token.is_kind(TokenKind::RightParen) {
+                break;
+            }
+            elem_types.push(self.parse_type());
+        }
+        self.expect_token(TokenKind::RightParen);
+        TupleType::new(elem_types).into()
+    }
+
     fn parse_func_type(&mut self) -> FuncType {
         let (param_list, is_var_args) = self.parse_param_list();
         self.expect_token(TokenKind::Arrow);
@@ -279,8 +734,23 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_ref_type(&mut self) -> RefType {
-        let expr = self.parse_expr();
-        RefType::new(expr)
+        let mut expr: Expr = self.parse_ident_expr().into();
+        while self.curr_token.is_kind(TokenKind::Dot) {
+            expr = self.parse_member_expr(expr).into();
+        }
+        let mut ref_type = RefType::new(expr);
+        if self.curr_token.is_kind(TokenKind::LeftChevron) {
+            self.accept_token();
+            loop {
+                ref_type.type_args.push(self.parse_type());
+                if !self.curr_token.is_kind(TokenKind::Comma) {
+                    break;
+                }
+                self.accept_token();
+            }
+            self.expect_token(TokenKind::RightChevron);
+        }
+        ref_type
     }
 
     fn parse_composite_type(&mut self) -> CompositeType {
@@ -291,26 +761,212 @@ impl<'a> Parser<'a> {
             false
         };
 
+        let mut interfaces = Vec::new();
+        if self.curr_token.is_kind(TokenKind::Implements) {
+            self.accept_token();
+            loop {
+                interfaces.push(self.parse_ref_type().into());
+                if !self.curr_token.is_kind(TokenKind::Comma) {
+                    break;
+                }
+                self.accept_token();
+            }
+        }
+
         self.expect_token(TokenKind::LeftBrace);
 
         let mut fields = Vec::new();
         loop {
-            if self.curr_token.is_kind(TokenKind::RightBrace) {
+            if self.curr_token.is_kind(TokenKind::RightBrace)
+                || self.curr_token.is_kind(TokenKind::Semicolon)
+            {
                 break;
             }
-            fields.push(self.parse_param());
+            fields.push(self.parse_field());
+        }
+
+        let mut statics = Vec::new();
+        let mut types = Vec::new();
+        if self.curr_token.is_kind(TokenKind::Semicolon) {
+            self.accept_token();
+            while !self.curr_token.is_kind(TokenKind::RightBrace) {
+                if self.curr_token.is_kind(TokenKind::Type) {
+                    types.push(self.parse_type_stmt());
+                } else {
+                    statics.push(self.parse_static());
+                }
+            }
+        }
+        self.expect_token(TokenKind::RightBrace);
+
+        let mut composite_type = CompositeType::new(fields, statics, is_restrict);
+        composite_type.interfaces = interfaces;
+        composite_type.types = types;
+        composite_type
+    }
+
+    fn parse_enum_type(&mut self) -> EnumType {
+        self.expect_token(TokenKind::Enum);
+        self.expect_token(TokenKind::LeftBrace);
+
+        let mut variants = Vec::new();
+        while !self.curr_token.is_kind(TokenKind::RightBrace) {
+            let ident = self.parse_ident();
+            let payload = if self.curr_token.is_kind(TokenKind::LeftParen) {
+                self.accept_token();
+                let payload = self.parse_type();
+                self.expect_token(TokenKind::RightParen);
+                Some(payload)
+            } else {
+                None
+            };
+            variants.push(Variant::new(ident.name, payload));
+
+            if self.curr_token.is_kind(TokenKind::Comma) {
+                self.accept_token();
+            }
+        }
+        self.expect_token(TokenKind::RightBrace);
+
+        EnumType::new(variants)
+    }
+
+    fn parse_interface_type(&mut self) -> InterfaceType {
+        self.expect_token(TokenKind::Interface);
+        self.expect_token(TokenKind::LeftBrace);
+
+        // Every member is a method, `self` is implied
+        let mut methods = Vec::new();
+        while !self.curr_token.is_kind(TokenKind::RightBrace) {
+            let method = self.parse_param();
+            if method.r#type.kind() != TypeKind::Func {
+                panic!("Interface member `{}` must be a method", method.name);
+            }
+            methods.push(method);
+
+            if self.curr_token.is_kind(TokenKind::Comma) {
+                self.accept_token();
+            }
         }
         self.expect_token(TokenKind::RightBrace);
 
-        CompositeType::new(fields, is_restrict)
+        InterfaceType::new(methods)
     }
 
     // ==================================================
 
-    fn parse_param_list(&mut self) -> (Vec<Param>, bool) {
-        self.expect_token(TokenKind::LeftParen);
+    fn parse_pattern(&mut self) -> Pattern {
+        let pattern = self.parse_primary_pattern();
+        if !self.curr_token.is_kind(TokenKind::Pipe) {
+            return pattern;
+        }
+
+        let mut patterns = vec![pattern];
+        while self.curr_token.is_kind(TokenKind::Pipe) {
+            self.accept_token();
+            patterns.push(self.parse_primary_pattern());
+        }
+        Pattern::Or(patterns)
+    }
+
+    fn parse_primary_pattern(&mut self) -> Pattern {
+        match self.curr_token.kind() {
+            TokenKind::IntLiteral => Pattern::IntLiteral(self.accept_token().spelling().to_owned()),
+            TokenKind::Minus => {
+                self.accept_token();
+                let token = self.expect_token(TokenKind::IntLiteral);
+                Pattern::IntLiteral(format!("-{}", token.spelling()))
+            }
+            TokenKind::BoolLiteral => {
+                Pattern::BoolLiteral(self.accept_token().spelling() == "true")
+            }
+            TokenKind::LeftParen => {
+                self.accept_token();
+                let pattern = self.parse_pattern();
+                if !self.curr_token.is_kind(TokenKind::Comma) {
+                    self.expect_token(TokenKind::RightParen);
+                    return pattern;
+                }
+
+                let mut patterns = vec![pattern];
+                while self.curr_token.is_kind(TokenKind::Comma) {
+                    self.accept_token();
+                    if self.curr_token.is_kind(TokenKind::RightParen) {
+                        break;
+                    }
+                    patterns.push(self.parse_pattern());
+                }
+                self.expect_token(TokenKind::RightParen);
+                Pattern::Tuple(TuplePattern::new(patterns))
+            }
+            TokenKind::Identifier if self.curr_token.spelling() == "_" => {
+                self.accept_token();
+                Pattern::Wildcard
+            }
+            TokenKind::Identifier => {
+                let ident = self.parse_ident();
+                if !self.curr_token.is_kind(TokenKind::Dot) {
+                    return Pattern::Binding(ident);
+                }
+
+                self.accept_token();
+                let mut owner: Expr = IdentExpr::new(ident).into();
+                let mut variant = self.parse_ident();
+                while self.curr_token.is_kind(TokenKind::Dot) {
+                    self.accept_token();
+                    owner = MemberExpr::new(owner, variant).into();
+                    variant = self.parse_ident();
+                }
+                let payload = if self.curr_token.is_kind(TokenKind::LeftParen) {
+                    self.accept_token();
+                    let payload = self.parse_pattern();
+                    self.expect_token(TokenKind::RightParen);
+                    Some(payload)
+                } else {
+                    None
+                };
+                Pattern::Variant(VariantPattern::new(owner, variant.name, payload))
+            }
+            _ => panic!(
+                "Unexpected token when parsing pattern: {}",
+                self.curr_token.spelling()
+            ),
+        }
+    }
+
+    // ==================================================
+
+    // The opening parenthesis is consumed by `parse_paren_type`
+    fn parse_type_params(&mut self) -> Vec<TypeParam> {
+        let mut type_params = Vec::new();
+        if !self.curr_token.is_kind(TokenKind::LeftChevron) {
+            return type_params;
+        }
+        self.accept_token();
+        loop {
+            let ident = self.parse_ident();
+            let mut bounds = Vec::new();
+            if self.curr_token.is_kind(TokenKind::Colon) {
+                self.accept_token();
+                bounds.push(self.parse_type());
+                while self.curr_token.is_kind(TokenKind::Plus) {
+                    self.accept_token();
+                    bounds.push(self.parse_type());
+                }
+            }
+            type_params.push(TypeParam::new(ident, bounds));
+            if !self.curr_token.is_kind(TokenKind::Comma) {
+                break;
+            }
+            self.accept_token();
+        }
+        self.expect_token(TokenKind::RightChevron);
+        type_params
+    }
 
+    fn parse_param_list(&mut self) -> (Vec<Param>, bool) {
         if self.curr_token.kind() == TokenKind::RightParen {
+            self.accept_token();
             return (Vec::new(), false);
         }
 
@@ -359,10 +1015,53 @@ impl<'a> Parser<'a> {
         Param::new(ident.name, r#type)
     }
 
+    fn parse_field(&mut self) -> Field {
+        let is_pub = if self.curr_token.is_kind(TokenKind::Pub) {
+            self.accept_token();
+            true
+        } else {
+            false
+        };
+
+        let is_inherit = if self.curr_token.is_kind(TokenKind::Inherit) {
+            self.accept_token();
+            true
+        } else {
+            false
+        };
+
+        let ident = self.parse_ident();
+        self.expect_token(TokenKind::Colon);
+        let r#type = self.parse_type();
+        Field::new(ident.name, r#type, is_pub, is_inherit)
+    }
+
+    fn parse_static(&mut self) -> Decl {
+        let is_pub = if self.curr_token.is_kind(TokenKind::Pub) {
+            self.accept_token();
+            true
+        } else {
+            false
+        };
+
+        let is_const = if self.curr_token.is_kind(TokenKind::Const) {
+            self.accept_token();
+            true
+        } else {
+            false
+        };
+
+        let mut decl = self.parse_decl();
+        decl.is_pub = is_pub;
+        decl.is_const = is_const;
+        decl
+    }
+
     fn parse_arg_list(&mut self) -> Vec<Arg> {
         self.expect_token(TokenKind::LeftParen);
 
         if self.curr_token.kind() == TokenKind::RightParen {
+            self.accept_token();
             return Vec::new();
         }
 
@@ -0,0 +1,35 @@
+use crate::ast::{
+    expr::IdentExpr,
+    ident::Ident,
+    stmt::{Stmt, TypeStmt},
+    type_param::TypeParam,
+    types::{EnumType, RefType, Type},
+    variant::Variant,
+};
+
+// Variant indices of `Result`, relied on by the `?` operator
+pub const RESULT_OK: u32 = 0;
+pub const RESULT_ERR: u32 = 1;
+
+// Definitions that every module can use without declaring them
+pub fn prelude() -> Vec<Stmt> {
+    vec![result_type_stmt().into()]
+}
+
+// type Result<T, E> enum { Ok(T) Err(E) }
+fn result_type_stmt() -> TypeStmt {
+    let param_type = |name: &str| -> Type {
+        RefType::new(IdentExpr::new(Ident::new(name.to_owned())).into()).into()
+    };
+    let enum_type = EnumType::new(vec![
+        Variant::new("Ok".to_owned(), Some(param_type("T"))),
+        Variant::new("Err".to_owned(), Some(param_type("E"))),
+    ]);
+
+    let mut type_stmt = TypeStmt::new(Ident::new("Result".to_owned()), enum_type.into());
+    type_stmt.type_params = ["T", "E"]
+        .iter()
+        .map(|name| TypeParam::new(Ident::new(name.to_string()), Vec::new()))
+        .collect();
+    type_stmt
+}
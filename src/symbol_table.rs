@@ -1,8 +1,10 @@
-use crate::ast::types::Type;
+use crate::ast::{decl::Decl, types::Type};
 
 pub struct TypeEntry {
     pub type_id: u64,
     pub name: String,
+    pub module: String,
+    pub r#type: Option<Type>,
     pub level: u64,
 }
 
@@ -13,19 +15,40 @@ pub struct ValueEntry {
     pub level: u64,
 }
 
+// A static member added to a type by an `extend` block
+pub struct ExtensionEntry {
+    pub type_id: u64,
+    pub module: String,
+    pub decl: Decl,
+    pub level: u64,
+}
+
 pub struct SymbolTable {
     level: u64,
     values: Vec<ValueEntry>,
     types: Vec<TypeEntry>,
+    extensions: Vec<ExtensionEntry>,
+    // Types of closed scopes can no longer be named but are still referred to by id
+    closed_types: Vec<TypeEntry>,
+    suspended_types: Vec<Vec<TypeEntry>>,
     next_id: u64,
 }
 
+pub struct SuspendedScopes {
+    level: u64,
+    values: Vec<ValueEntry>,
+    extensions: Vec<ExtensionEntry>,
+}
+
 impl SymbolTable {
     pub fn new() -> Self {
         Self {
             level: 1,
             values: Vec::new(),
             types: Vec::new(),
+            extensions: Vec::new(),
+            closed_types: Vec::new(),
+            suspended_types: Vec::new(),
             next_id: 0,
         }
     }
@@ -40,32 +63,103 @@ impl SymbolTable {
             self.values.pop();
             last = self.values.last();
         }
+        while self
+            .types
+            .last()
+            .is_some_and(|entry| entry.level == self.level)
+        {
+            self.closed_types.push(self.types.pop().unwrap());
+        }
+        while self
+            .extensions
+            .last()
+            .is_some_and(|entry| entry.level == self.level)
+        {
+            self.extensions.pop();
+        }
         self.level -= 1;
     }
 
-    pub fn push_value(&mut self, name: &str, r#type: Option<Type>) -> u64 {
+    // Hides every local scope so that only global symbols are visible, e.g. while
+    // checking an instance of a generic definition away from its use site
+    pub fn suspend_scopes(&mut self) -> SuspendedScopes {
+        let values_len = self.values.partition_point(|entry| entry.level <= 1);
+        let types_len = self.types.partition_point(|entry| entry.level <= 1);
+        let extensions_len = self.extensions.partition_point(|entry| entry.level <= 1);
+        let suspended_types = self.types.split_off(types_len);
+        self.suspended_types.push(suspended_types);
+        let suspended = SuspendedScopes {
+            level: self.level,
+            values: self.values.split_off(values_len),
+            extensions: self.extensions.split_off(extensions_len),
+        };
+        self.level = 1;
+        suspended
+    }
+
+    pub fn resume_scopes(&mut self, suspended: SuspendedScopes) {
+        self.level = suspended.level;
+        self.values.extend(suspended.values);
+        self.extensions.extend(suspended.extensions);
+        let suspended_types = self.suspended_types.pop().unwrap();
+        self.types.extend(suspended_types);
+    }
+
+    pub fn is_global_scope(&self) -> bool {
+        self.level == 1
+    }
+
+    pub fn new_id(&mut self) -> u64 {
         let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    pub fn push_value(&mut self, name: &str, r#type: Option<Type>) -> u64 {
+        let id = self.new_id();
         self.values.push(ValueEntry {
             value_id: id,
             name: name.to_owned(),
             r#type,
             level: self.level,
         });
-        self.next_id += 1;
         id
     }
 
-    pub fn push_type(&mut self, name: &str) -> u64 {
-        let id = self.next_id;
+    pub fn push_type(&mut self, name: &str, module: &str) -> u64 {
+        let id = self.new_id();
         self.types.push(TypeEntry {
             type_id: id,
             name: name.to_owned(),
+            module: module.to_owned(),
+            r#type: None,
             level: self.level,
         });
-        self.next_id += 1;
         id
     }
 
+    pub fn push_extension(&mut self, type_id: u64, module: &str, decl: Decl) {
+        self.extensions.push(ExtensionEntry {
+            type_id,
+            module: module.to_owned(),
+            decl,
+            level: self.level,
+        });
+    }
+
+    pub fn define_type(&mut self, type_id: u64, r#type: Type) {
+        if let Some(entry) = self
+            .types
+            .iter_mut()
+            .chain(&mut self.closed_types)
+            .chain(self.suspended_types.iter_mut().flatten())
+            .rev()
+            .find(|entry| entry.type_id == type_id)
+        {
+            entry.r#type = Some(r#type);
+        }
+    }
+
     pub fn retrieve_value(&self, name: &str) -> Option<&ValueEntry> {
         for entry in self.values.iter().rev() {
             if entry.name == name {
@@ -84,6 +178,38 @@ impl SymbolTable {
         None
     }
 
+    pub fn retrieve_module_type(&self, name: &str, module: &str) -> Option<&TypeEntry> {
+        self.types
+            .iter()
+            .rev()
+            .find(|entry| entry.level == 1 && entry.name == name && entry.module == module)
+    }
+
+    // Names visible from the current scope, innermost first
+    pub fn value_names(&self) -> impl Iterator<Item = &str> {
+        self.values.iter().rev().map(|entry| entry.name.as_str())
+    }
+
+    pub fn type_names(&self) -> impl Iterator<Item = &str> {
+        self.types.iter().rev().map(|entry| entry.name.as_str())
+    }
+
+    pub fn retrieve_extension(&self, type_id: u64, name: &str) -> Option<&ExtensionEntry> {
+        self.extensions
+            .iter()
+            .rev()
+            .find(|entry| entry.type_id == type_id && entry.decl.name == name)
+    }
+
+    pub fn retrieve_type_by_id(&self, type_id: u64) -> Option<&TypeEntry> {
+        self.types
+            .iter()
+            .chain(&self.closed_types)
+            .chain(self.suspended_types.iter().flatten())
+            .rev()
+            .find(|entry| entry.type_id == type_id)
+    }
+
     pub fn retrieve_value_same_level(&self, name: &str) -> Option<&ValueEntry> {
         for entry in self.values.iter().rev() {
             if entry.level != self.level {
@@ -33,34 +33,55 @@ impl Token {
     pub fn begin(&self) -> CursorPos {
         self.begin
     }
+
+    pub fn end(&self) -> CursorPos {
+        self.end
+    }
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum TokenKind {
     // Keywords
+    Const,
+    Else,
+    Enum,
+    Extend,
     Extern,
     Export,
     Import,
+    If,
+    Implements,
+    Inherit,
+    Interface,
     Let,
+    Match,
     Mut,
+    Null,
+    Pub,
     Restrict,
     Return,
     Type,
 
     // Primitive types
+    Bool,
     I8,
     I32,
     I64,
+    Never,
+    Void,
 
     // Separators
     Assign,
     Arrow,
+    FatArrow,
     Comma,
     Dot,
     Colon,
     Semicolon,
     To,
     Ellipsis,
+    Pipe,
+    Question,
 
     // Operators
     Plus,
@@ -70,8 +91,11 @@ pub enum TokenKind {
     Divide,
     Equal,
     NotEqual,
+    LessEqual,
+    GreaterEqual,
     Increment,
     Decrement,
+    Coalesce,
 
     // Brackets
     LeftBrace,